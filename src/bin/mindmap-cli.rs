@@ -0,0 +1,84 @@
+//! 命令行工具：不打开窗口，直接读取导图文件做格式转换、校验和统计
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use egui_app::io::{self, Format};
+
+const USAGE: &str = "\
+usage:
+  mindmap-cli convert <input> <output> [--from <format>] [--to <format>]
+  mindmap-cli validate <file> [--from <format>]
+  mindmap-cli stats <file> [--from <format>]
+
+formats: mindmap (native), md, opml, mm (FreeMind), svg, png
+the format is detected from the file extension unless --from/--to is given";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(code) => code,
+        Err(message) => {
+            eprintln!("error: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<ExitCode, String> {
+    let mut positional = Vec::new();
+    let mut from = None;
+    let mut to = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--from" => from = Some(parse_format(iter.next())?),
+            "--to" => to = Some(parse_format(iter.next())?),
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(ExitCode::SUCCESS);
+            }
+            _ => positional.push(arg.as_str()),
+        }
+    }
+
+    match positional.as_slice() {
+        ["convert", input, output] => {
+            let graph = io::load(Path::new(input), from)?;
+            io::save(&graph, &PathBuf::from(output), to)?;
+            println!("converted {input} -> {output} ({} nodes)", graph.nodes.len());
+            Ok(ExitCode::SUCCESS)
+        }
+        ["validate", file] => {
+            let graph = io::load(Path::new(file), from)?;
+            let base_dir = Path::new(file).parent().unwrap_or(Path::new("."));
+            let mut problems = graph.validate();
+            problems.extend(io::check_links(&graph, base_dir).into_iter().map(|(id, problem)| format!("{id}: {problem}")));
+            if problems.is_empty() {
+                println!("{file}: ok ({} nodes)", graph.nodes.len());
+                Ok(ExitCode::SUCCESS)
+            } else {
+                for problem in &problems {
+                    println!("{file}: {problem}");
+                }
+                println!("{file}: {} problem(s) found", problems.len());
+                Ok(ExitCode::FAILURE)
+            }
+        }
+        ["stats", file] => {
+            let graph = io::load(Path::new(file), from)?;
+            let stats = graph.stats();
+            println!("nodes:     {}", stats.nodes);
+            println!("roots:     {}", stats.roots);
+            println!("leaves:    {}", stats.leaves);
+            println!("max depth: {}", stats.max_depth);
+            println!("links:     {}", stats.links);
+            Ok(ExitCode::SUCCESS)
+        }
+        _ => Err(format!("invalid arguments\n{USAGE}")),
+    }
+}
+
+fn parse_format(name: Option<&String>) -> Result<Format, String> {
+    let name = name.ok_or("missing format name")?;
+    Format::from_name(name).ok_or_else(|| format!("unknown format '{name}'"))
+}
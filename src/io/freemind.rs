@@ -0,0 +1,175 @@
+//! FreeMind `.mm` 文件，每个节点对应一个 `<node TEXT="...">`，备注是节点里的 `<richcontent TYPE="NOTE">` HTML，
+//! 到父节点连线上的文字和概要节点括起的节点数是节点的 `<attribute NAME="connector_label">`、
+//! `<attribute NAME="summary">` 属性
+
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use super::Outline;
+
+//保存连线文字和概要括起的节点数的节点属性名，OPML 里加上下划线前缀
+const LABEL_ATTRIBUTE: &str = "connector_label";
+const SUMMARY_ATTRIBUTE: &str = "summary";
+
+//FreeMind 只允许一个根节点，有多个根节点时用文件名作为新的根节点把它们包起来
+pub fn write(outlines: &[Outline], title: &str) -> String {
+    let mut out = String::from("<map version=\"1.0.1\">\n");
+    match outlines {
+        [root] => write_nodes(&mut out, std::slice::from_ref(root), 0),
+        _ => {
+            out.push_str(&format!("  <node TEXT=\"{}\">\n", escape(title)));
+            write_nodes(&mut out, outlines, 1);
+            out.push_str("  </node>\n");
+        }
+    }
+    out.push_str("</map>\n");
+    out
+}
+
+//写出 level 层的一组节点和它们的子树，用显式的栈代替递归；Some 是要写出的节点，None 是要写出的结束标签
+fn write_nodes(out: &mut String, outlines: &[Outline], level: usize) {
+    let mut stack: Vec<(Option<&Outline>, usize)> = outlines.iter().rev().map(|outline| (Some(outline), level)).collect();
+    while let Some((outline, level)) = stack.pop() {
+        let indent = super::xml_indent(level + 1);
+        let Some(outline) = outline else {
+            out.push_str(&format!("{indent}</node>\n"));
+            continue;
+        };
+        out.push_str(&format!("{indent}<node TEXT=\"{}\"", escape(outline.text.as_str())));
+        if level == 1 {
+            out.push_str(" POSITION=\"right\"");
+        }
+        if outline.children.is_empty() && outline.note.is_empty() && outline.label.is_empty() && outline.summary == 0 {
+            out.push_str("/>\n");
+            continue;
+        }
+        out.push_str(">\n");
+        if !outline.note.is_empty() {
+            out.push_str(&format!("{indent}  <richcontent TYPE=\"NOTE\"><html><head/><body>"));
+            for line in outline.note.lines() {
+                out.push_str(&format!("<p>{}</p>", escape(line)));
+            }
+            out.push_str("</body></html></richcontent>\n");
+        }
+        if !outline.label.is_empty() {
+            out.push_str(&format!("{indent}  <attribute NAME=\"{LABEL_ATTRIBUTE}\" VALUE=\"{}\"/>\n", super::xml_attribute(&outline.label)));
+        }
+        if outline.summary > 0 {
+            out.push_str(&format!("{indent}  <attribute NAME=\"{SUMMARY_ATTRIBUTE}\" VALUE=\"{}\"/>\n", outline.summary));
+        }
+        stack.push((None, level));
+        stack.extend(outline.children.iter().rev().map(|child| (Some(child), level + 1)));
+    }
+}
+
+pub fn parse(src: &str) -> Result<Vec<Outline>, String> {
+    parse_xml_tree(src, b"node", b"TEXT", false)
+}
+
+//OPML 的 `_note` 等属性或 FreeMind 的 `<attribute>` 元素里的附加信息，其它名字忽略
+fn set_attribute(outline: &mut Outline, name: &str, value: String) -> Result<(), String> {
+    match name {
+        "note" => outline.note = value,
+        LABEL_ATTRIBUTE => outline.label = value,
+        SUMMARY_ATTRIBUTE => outline.summary = value.trim().parse().map_err(|_| format!("invalid summary size {value:?}"))?,
+        _ => {}
+    }
+    Ok(())
+}
+
+//读取名为 element 的嵌套元素，用 attribute 属性作为节点文本，`<richcontent TYPE="NOTE">` 里的 HTML 文字作为备注，
+//`<attribute NAME=... VALUE=...>` 作为连线文字等附加信息；prefixed 为 true 时元素上以下划线开头的属性
+//（`_note`、`_connector_label`、`_summary`）也是附加信息，其它元素忽略
+pub(super) fn parse_xml_tree(src: &str, element: &[u8], attribute: &[u8], prefixed: bool) -> Result<Vec<Outline>, String> {
+    let attribute_of = |e: &BytesStart, name: &[u8]| -> Result<String, String> {
+        match e.try_get_attribute(name).map_err(|e| e.to_string())? {
+            Some(attr) => Ok(attr.unescape_value().map_err(|e| e.to_string())?.into_owned()),
+            None => Ok(String::new()),
+        }
+    };
+    let text_of = |e: &BytesStart| -> Result<Outline, String> {
+        let mut outline = Outline::new(attribute_of(e, attribute)?);
+        if prefixed {
+            for attr in e.attributes() {
+                let attr = attr.map_err(|e| e.to_string())?;
+                if let Some(name) = attr.key.as_ref().strip_prefix(b"_") {
+                    let value = attr.unescape_value().map_err(|e| e.to_string())?.into_owned();
+                    set_attribute(&mut outline, &String::from_utf8_lossy(name), value)?;
+                }
+            }
+        }
+        Ok(outline)
+    };
+    let mut reader = Reader::from_str(src);
+    let mut roots = Vec::new();
+    let mut stack: Vec<Outline> = Vec::new();
+    //正在读取的备注 HTML 里的文字，段落之间用换行隔开
+    let mut note: Option<String> = None;
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| format!("xml error at position {}: {e}", reader.error_position()))?;
+        match event {
+            Event::Start(e) if e.local_name().as_ref() == b"richcontent" => {
+                note = (attribute_of(&e, b"TYPE")? == "NOTE").then(String::new);
+            }
+            Event::End(e) if e.local_name().as_ref() == b"richcontent" => {
+                if let (Some(html), Some(outline)) = (note.take(), stack.last_mut()) {
+                    outline.note = html_lines(&html);
+                }
+            }
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"attribute" => {
+                if let Some(outline) = stack.last_mut().filter(|_| note.is_none()) {
+                    set_attribute(outline, &attribute_of(&e, b"NAME")?, attribute_of(&e, b"VALUE")?)?;
+                }
+            }
+            Event::Start(e) | Event::Empty(e) if matches!(e.local_name().as_ref(), b"p" | b"br" | b"li" | b"div") => {
+                if let Some(note) = note.as_mut() {
+                    note.push('\n');
+                }
+            }
+            Event::Text(e) => {
+                let Some(note) = note.as_mut() else { continue };
+                let text = e.unescape().map_err(|e| e.to_string())?;
+                //HTML 里连续的空白算一个空格
+                let mut words = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                if text.starts_with(char::is_whitespace) {
+                    words.insert(0, ' ');
+                }
+                if text.ends_with(char::is_whitespace) && !words.ends_with(' ') {
+                    words.push(' ');
+                }
+                note.push_str(&words);
+            }
+            Event::Start(e) if e.local_name().as_ref() == element => stack.push(text_of(&e)?),
+            Event::Empty(e) if e.local_name().as_ref() == element => {
+                let outline = text_of(&e)?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(outline),
+                    None => roots.push(outline),
+                }
+            }
+            Event::End(e) if e.local_name().as_ref() == element => {
+                let outline = stack.pop().ok_or("unbalanced xml elements")?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(outline),
+                    None => roots.push(outline),
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    if !stack.is_empty() {
+        return Err("unexpected end of xml".to_string());
+    }
+    Ok(roots)
+}
+
+//去掉每行两端的空白和首尾的空行
+fn html_lines(text: &str) -> String {
+    let lines: Vec<&str> = text.split('\n').map(str::trim).collect();
+    let first = lines.iter().position(|line| !line.is_empty()).unwrap_or(lines.len());
+    let last = lines.iter().rposition(|line| !line.is_empty()).map_or(first, |i| i + 1);
+    lines[first..last].join("\n")
+}
@@ -0,0 +1,166 @@
+//! Markdown 大纲：根节点写成一级标题，子节点写成嵌套的无序列表，备注写成跟在后面的引用块，
+//! 到父节点连线上的文字写成列表项末尾的 `<!-- label: ... -->` 注释，
+//! 概要节点是跟在括起来的兄弟节点后面、带 `<!-- summary: 括起的节点数 -->` 注释的列表项
+
+use super::Outline;
+
+//每一级列表缩进两个空格
+const INDENT: &str = "  ";
+//连线文字和概要注释的开头，以及注释的结尾
+const LABEL_START: &str = "<!-- label: ";
+const SUMMARY_START: &str = "<!-- summary: ";
+const COMMENT_END: &str = " -->";
+
+pub fn write(outlines: &[Outline]) -> String {
+    let mut out = String::new();
+    for (i, root) in outlines.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        out.push_str("# ");
+        out.push_str(&single_line(&root.text));
+        out.push_str("\n\n");
+        if !root.note.is_empty() {
+            write_note(&mut out, &root.note, "");
+            out.push('\n');
+        }
+        //用显式的栈按先序写出列表项，嵌套很深也不会栈溢出
+        let mut stack: Vec<(&Outline, usize)> = root.children.iter().rev().map(|child| (child, 0)).collect();
+        while let Some((outline, level)) = stack.pop() {
+            out.push_str(&INDENT.repeat(level));
+            out.push_str("- ");
+            out.push_str(&single_line(&outline.text));
+            if outline.summary > 0 {
+                out.push_str(&format!(" {SUMMARY_START}{}{COMMENT_END}", outline.summary));
+            }
+            if !outline.label.is_empty() {
+                out.push_str(&format!(" {LABEL_START}{}{COMMENT_END}", escape_comment(&outline.label)));
+            }
+            out.push('\n');
+            write_note(&mut out, &outline.note, &INDENT.repeat(level + 1));
+            stack.extend(outline.children.iter().rev().map(|child| (child, level + 1)));
+        }
+    }
+    out
+}
+
+//备注的每一行写成 "> line"，和节点的子项对齐
+fn write_note(out: &mut String, note: &str, indent: &str) {
+    for line in note.lines() {
+        out.push_str(indent);
+        out.push('>');
+        if !line.is_empty() {
+            out.push(' ');
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+}
+
+//标题按级别嵌套，列表项按缩进嵌套在最近的标题下面，引用块作为前一个节点的备注，其它文本忽略
+pub fn parse(src: &str) -> Vec<Outline> {
+    let mut roots = Vec::new();
+    //当前路径上每一层节点的 (层级, 节点)
+    let mut stack: Vec<(usize, Outline)> = Vec::new();
+    let mut heading_level = 0;
+    for line in src.lines() {
+        let trimmed = line.trim_start();
+        let (level, text) = if let Some(text) = heading_text(trimmed) {
+            heading_level = trimmed.chars().take_while(|c| *c == '#').count();
+            (heading_level, text)
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            if let Some((_, outline)) = stack.last_mut() {
+                if !outline.note.is_empty() {
+                    outline.note.push('\n');
+                }
+                outline.note.push_str(quote.strip_prefix(' ').unwrap_or(quote));
+            }
+            continue;
+        } else if let Some(text) = list_item_text(trimmed) {
+            let indent = line[..line.len() - trimmed.len()].replace('\t', INDENT).len() / INDENT.len();
+            (heading_level + 1 + indent, text)
+        } else {
+            continue;
+        };
+        while stack.last().is_some_and(|(top, _)| *top >= level) {
+            pop_into(&mut stack, &mut roots);
+        }
+        let (text, label) = split_comment(text.trim(), LABEL_START);
+        let (text, summary) = split_comment(text, SUMMARY_START);
+        let mut outline = Outline::new(text);
+        outline.label = label.map(unescape_comment).unwrap_or_default();
+        outline.summary = summary.and_then(|summary| summary.trim().parse().ok()).unwrap_or(0);
+        stack.push((level, outline));
+    }
+    while !stack.is_empty() {
+        pop_into(&mut stack, &mut roots);
+    }
+    roots
+}
+
+fn pop_into(stack: &mut Vec<(usize, Outline)>, roots: &mut Vec<Outline>) {
+    let (_, outline) = stack.pop().expect("stack is not empty");
+    match stack.last_mut() {
+        Some((_, parent)) => parent.children.push(outline),
+        None => roots.push(outline),
+    }
+}
+
+//把末尾以 start 开头的注释和前面的文字分开
+fn split_comment<'a>(text: &'a str, start: &str) -> (&'a str, Option<&'a str>) {
+    text.strip_suffix(COMMENT_END)
+        .and_then(|rest| rest.rsplit_once(start))
+        .map_or((text, None), |(text, comment)| (text.trim_end(), Some(comment)))
+}
+
+//注释里不能出现 "--" 和换行：反斜杠写成 \\，换行写成 \n，紧跟在 '-' 后面的 '-' 写成 \-
+fn escape_comment(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '-' if out.ends_with('-') => out.push_str("\\-"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+//escape_comment 的逆操作
+fn unescape_comment(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some(next) => out.push(next),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+fn heading_text(line: &str) -> Option<&str> {
+    let rest = line.trim_start_matches('#');
+    (rest.len() < line.len() && rest.starts_with(' ')).then_some(rest)
+}
+
+fn list_item_text(line: &str) -> Option<&str> {
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(text) = line.strip_prefix(bullet) {
+            return Some(text);
+        }
+    }
+    //有序列表 "1. text"
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    (digits > 0).then(|| line[digits..].strip_prefix(". ")).flatten()
+}
+
+fn single_line(text: &str) -> String {
+    text.lines().map(str::trim).collect::<Vec<_>>().join(" ")
+}
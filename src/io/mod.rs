@@ -0,0 +1,540 @@
+//! 思维导图文件的读写与格式转换
+//!
+//! 原生格式是 `NodeGraph` 的 JSON 序列化，其余格式都先转换成 `Outline` 大纲树，
+//! 再由大纲树导入或导出，这样每种格式只需要关心自己的语法。
+
+pub mod markdown;
+pub mod opml;
+pub mod freemind;
+pub mod svg;
+pub mod png;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use ab_glyph::{Font, FontRef, GlyphId, PxScale, ScaleFont};
+use serde::{Deserialize, Serialize};
+use crate::model::boundary::polygon_bounds;
+use crate::model::rich_text::{parse_lines, wrap_spans};
+use crate::model::{Hyperlink, Node, NodeGraph, NodeId, Point, Rect, TextLayout, TextSpan, Vector};
+
+/// 原生文件格式的版本号，格式不兼容时加一
+pub const FORMAT_VERSION: u32 = 1;
+
+/// 导入时同一层级节点之间的水平距离，和 `add_node_with_father_node` 的默认偏移一致
+const LAYOUT_LEVEL_SPACING: f32 = 300.0;
+/// 导入时相邻叶子节点之间的垂直距离
+const LAYOUT_ROW_SPACING: f32 = 80.0;
+/// XML 格式导出时缩进的最大层数
+const MAX_XML_INDENT: usize = 32;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Format {
+    Native,
+    Markdown,
+    Opml,
+    FreeMind,
+    Svg,
+    Png,
+}
+
+impl Format {
+    //根据文件扩展名判断格式
+    pub fn from_path(path: &Path) -> Option<Format> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        Format::from_name(&ext)
+    }
+    //根据名称判断格式，命令行里的 --format 参数也用这个
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name.to_ascii_lowercase().as_str() {
+            "mindmap" | "json" | "native" => Some(Format::Native),
+            "md" | "markdown" => Some(Format::Markdown),
+            "opml" => Some(Format::Opml),
+            "mm" | "freemind" => Some(Format::FreeMind),
+            "svg" => Some(Format::Svg),
+            "png" => Some(Format::Png),
+            _ => None,
+        }
+    }
+    //svg 和 png 只能导出，不能导入
+    pub fn can_import(&self) -> bool {
+        !matches!(self, Format::Svg | Format::Png)
+    }
+}
+
+/// 与具体格式无关的大纲树，文本格式的导入导出都经过它
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Outline {
+    pub text: String,
+    /// 节点的备注，可以有多行
+    pub note: String,
+    /// 到父节点连线上的文字
+    pub label: String,
+    /// 大于 0 时这是一个概要节点，括起它前面这么多个普通的兄弟节点
+    pub summary: usize,
+    pub children: Vec<Outline>,
+}
+
+impl Outline {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self { text: text.into(), note: String::new(), label: String::new(), summary: 0, children: Vec::new() }
+    }
+}
+
+//默认的析构会按层级递归，导入的文件嵌套很深时会栈溢出，这里把子树摊平后再逐个释放
+impl Drop for Outline {
+    fn drop(&mut self) {
+        let mut pending = std::mem::take(&mut self.children);
+        while let Some(mut outline) = pending.pop() {
+            pending.append(&mut outline.children);
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct NativeFileRef<'a> {
+    version: u32,
+    graph: &'a NodeGraph,
+}
+
+#[derive(Deserialize)]
+struct NativeFile {
+    version: u32,
+    graph: NodeGraph,
+}
+
+//原生格式序列化
+pub fn to_native(graph: &NodeGraph) -> Result<String, String> {
+    serde_json::to_string_pretty(&NativeFileRef { version: FORMAT_VERSION, graph })
+        .map_err(|e| format!("failed to serialize map: {e}"))
+}
+
+//原生格式反序列化
+pub fn from_native(src: &str) -> Result<NodeGraph, String> {
+    let file: NativeFile = serde_json::from_str(src).map_err(|e| format!("invalid map file: {e}"))?;
+    if file.version > FORMAT_VERSION {
+        return Err(format!(
+            "map file version {} is newer than supported version {FORMAT_VERSION}",
+            file.version
+        ));
+    }
+    Ok(file.graph)
+}
+
+/// 按文件扩展名（或指定的格式）读取导图
+pub fn load(path: &Path, format: Option<Format>) -> Result<NodeGraph, String> {
+    let format = format
+        .or_else(|| Format::from_path(path))
+        .ok_or_else(|| format!("cannot detect format of {}", path.display()))?;
+    if !format.can_import() {
+        return Err(format!("{format:?} files can only be exported"));
+    }
+    let src = fs::read_to_string(path).map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    match format {
+        Format::Native => from_native(&src),
+        Format::Markdown => Ok(graph_from_outlines(&markdown::parse(&src))),
+        Format::Opml => Ok(graph_from_outlines(&opml::parse(&src)?)),
+        Format::FreeMind => Ok(graph_from_outlines(&freemind::parse(&src)?)),
+        Format::Svg | Format::Png => unreachable!(),
+    }
+}
+
+/// 按文件扩展名（或指定的格式）保存导图
+pub fn save(graph: &NodeGraph, path: &Path, format: Option<Format>) -> Result<(), String> {
+    let format = format
+        .or_else(|| Format::from_path(path))
+        .ok_or_else(|| format!("cannot detect format of {}", path.display()))?;
+    let bytes = match format {
+        Format::Native => to_native(graph)?.into_bytes(),
+        Format::Markdown => markdown::write(&outlines_from_graph(graph)).into_bytes(),
+        Format::Opml => opml::write(&outlines_from_graph(graph), &title_of(path)).into_bytes(),
+        Format::FreeMind => freemind::write(&outlines_from_graph(graph), &title_of(path)).into_bytes(),
+        Format::Svg => svg::write(graph).into_bytes(),
+        Format::Png => png::write(graph)?,
+    };
+    fs::write(path, bytes).map_err(|e| format!("failed to write {}: {e}", path.display()))
+}
+
+/// 检查超链接指向的本地文件和其它导图里的节点是否存在，相对路径相对于 base_dir（导图文件所在的目录）
+/// 返回失效链接所在的节点和原因；指向本导图的链接见 `NodeGraph::broken_links`
+pub fn check_links(graph: &NodeGraph, base_dir: &Path) -> Vec<(NodeId, String)> {
+    //同一个导图文件只读一次
+    let mut maps: HashMap<PathBuf, Result<NodeGraph, String>> = HashMap::new();
+    let mut problems = Vec::new();
+    for (id, node) in &graph.nodes {
+        match &node.hyperlink {
+            Some(Hyperlink::File(path)) if !Hyperlink::resolve(base_dir, path).exists() => {
+                problems.push((id, format!("linked file {path} does not exist")));
+            }
+            Some(Hyperlink::Node { file: Some(file), node }) => {
+                let path = Hyperlink::resolve(base_dir, file);
+                match maps.entry(path.clone()).or_insert_with(|| load(&path, None)) {
+                    Ok(map) if map.nodes.contains_key(*node) => {}
+                    Ok(_) => problems.push((id, format!("linked map {file} has no node {node}"))),
+                    Err(err) => problems.push((id, err.clone())),
+                }
+            }
+            _ => {}
+        }
+    }
+    problems.sort_unstable_by_key(|(id, _)| *id);
+    problems
+}
+
+//XML 导出时第 level 层的缩进，超过 MAX_XML_INDENT 层后不再加深，免得很深的树导出的文件大小按深度的平方增长
+fn xml_indent(level: usize) -> String {
+    "  ".repeat(level.min(MAX_XML_INDENT))
+}
+
+//XML 属性值：转义特殊字符，换行写成字符引用，否则读取时会被规范化成空格
+fn xml_attribute(text: &str) -> String {
+    quick_xml::escape::escape(text).replace('\n', "&#10;")
+}
+
+fn title_of(path: &Path) -> String {
+    path.file_stem().and_then(|s| s.to_str()).unwrap_or("mind map").to_string()
+}
+
+//把导图转换成大纲树，兄弟节点按屏幕上的位置从上到下排列，
+//概要节点作为 summary 大于 0 的子节点跟在它括起来的最后一个兄弟节点后面
+pub fn outlines_from_graph(graph: &NodeGraph) -> Vec<Outline> {
+    let outline_of = |id: NodeId, summary: usize| {
+        let mut outline = Outline::new(graph.nodes[id].node_text.as_str());
+        outline.note = graph.nodes[id].note.clone();
+        outline.label = graph.nodes[id].connector_label.clone();
+        outline.summary = summary;
+        outline
+    };
+    //每个父节点下的概要：括起来的兄弟节点和概要节点
+    let mut summaries: HashMap<NodeId, Vec<(Vec<NodeId>, NodeId)>> = HashMap::new();
+    for (id, summary) in &graph.summaries {
+        let range = graph.summary_range(id);
+        if let Some(father_id) = range.first().and_then(|first| graph.nodes[*first].father_id) {
+            summaries.entry(father_id).or_default().push((range, summary.topic));
+        }
+    }
+    //子节点和它们之间的概要节点，以及概要节点括起的节点数
+    let children_of = |id: NodeId| {
+        let mut children: Vec<(NodeId, usize)> = graph.ordered_children(id).into_iter().map(|child| (child, 0)).collect();
+        for (range, topic) in summaries.get(&id).into_iter().flatten() {
+            let last = children.iter().rposition(|(child, count)| *count == 0 && range.contains(child));
+            if let Some(last) = last {
+                let count = children[..=last].iter().filter(|(_, count)| *count == 0).count().min(range.len());
+                children.insert(last + 1, (*topic, count));
+            }
+        }
+        children.into_iter()
+    };
+    let mut roots = Vec::new();
+    for root in graph.root_nodes() {
+        //用显式的栈代替递归，栈里是正在构建的节点和它还没处理的子节点
+        let mut stack = vec![(outline_of(root, 0), children_of(root))];
+        while let Some((_, children)) = stack.last_mut() {
+            if let Some((child, summary)) = children.next() {
+                stack.push((outline_of(child, summary), children_of(child)));
+                continue;
+            }
+            let (outline, _) = stack.pop().expect("stack is not empty");
+            match stack.last_mut() {
+                Some((parent, _)) => parent.children.push(outline),
+                None => roots.push(outline),
+            }
+        }
+    }
+    roots
+}
+
+//把大纲树导入成导图，并做一个简单的从左到右的树形布局
+pub fn graph_from_outlines(outlines: &[Outline]) -> NodeGraph {
+    let mut graph = NodeGraph::default();
+    let mut next_row = 0.0;
+    for outline in outlines {
+        let root = graph.add_node_with_position(Point::ZERO);
+        place_outline(&mut graph, root, outline, &mut next_row);
+        next_row += 1.0; //不同的根节点之间多空一行
+    }
+    //概要节点移到括号外面，它的子树跟着一起移动
+    let topics: Vec<(NodeId, Point)> = graph.summaries.values().map(|summary| (summary.topic, graph.nodes[summary.topic].node_pos)).collect();
+    graph.layout_summaries();
+    for (topic, old_pos) in topics {
+        let offset = graph.nodes[topic].node_pos - old_pos;
+        for id in graph.query_all_children_nodes(topic) {
+            let pos = graph.nodes[id].node_pos + offset;
+            graph.set_position(id, pos);
+        }
+    }
+    graph
+}
+
+//放置一棵大纲树里的节点，叶子节点依次占用一行，父节点放在子节点的中间；
+//概要节点括起它前面的普通兄弟节点，位置最后由 layout_summaries 计算
+fn place_outline(graph: &mut NodeGraph, root: NodeId, outline: &Outline, next_row: &mut f32) {
+    //正在放置的节点：大纲、节点、下一个要放置的子节点、已放置的普通子节点，以及它们中第一个和最后一个的 y 坐标
+    struct Frame<'a> {
+        outline: &'a Outline,
+        id: NodeId,
+        next: usize,
+        children: Vec<NodeId>,
+        first: Option<f32>,
+        last: f32,
+    }
+    //设置节点的文字和备注，并按文字和所在层级的字号计算大小，depth 从 1 开始
+    fn frame<'a>(graph: &mut NodeGraph, id: NodeId, outline: &'a Outline, depth: usize, font: &FontRef) -> Frame<'a> {
+        let font_size = graph.theme.node_style(depth).font_size;
+        let node = &mut graph.nodes[id];
+        node.node_text = outline.text.clone();
+        node.note = outline.note.clone();
+        node.connector_label = outline.label.clone();
+        node.node_size = fit_text_size(node, font, font_size);
+        Frame { outline, id, next: 0, children: Vec::new(), first: None, last: 0.0 }
+    }
+    let font = export_font();
+    //用显式的栈代替递归，嵌套很深的文件也不会栈溢出
+    let mut stack = vec![frame(graph, root, outline, 1, &font)];
+    while let Some(top) = stack.last_mut() {
+        if let Some(child) = top.outline.children.get(top.next) {
+            top.next += 1;
+            let child_id = match top.children.len().checked_sub(child.summary) {
+                //括起的节点数超出已有的兄弟节点时当作普通节点
+                Some(start) if child.summary > 0 => {
+                    let topic = graph.add_node_with_position(Point::ZERO);
+                    let (from, to) = (top.children[start], top.children[top.children.len() - 1]);
+                    graph.add_summary_topic(from, to, topic);
+                    topic
+                }
+                _ => {
+                    let child_id = graph.add_node_with_father_node(top.id);
+                    top.children.push(child_id);
+                    child_id
+                }
+            };
+            let depth = stack.len() + 1;
+            stack.push(frame(graph, child_id, child, depth, &font));
+            continue;
+        }
+        let top = stack.pop().expect("stack is not empty");
+        let y = if top.outline.children.is_empty() {
+            let y = *next_row * LAYOUT_ROW_SPACING;
+            *next_row += 1.0;
+            y
+        } else {
+            (top.first.unwrap_or(top.last) + top.last) / 2.0
+        };
+        graph.nodes[top.id].set_pos(Point::new(stack.len() as f32 * LAYOUT_LEVEL_SPACING, y));
+        if let Some(parent) = stack.last_mut().filter(|parent| parent.children.last() == Some(&top.id)) {
+            parent.last = y;
+            parent.first.get_or_insert(y);
+        }
+    }
+}
+
+//导出时测量和绘制文字的字体，和画布上 egui 的默认字体一样
+fn export_font() -> FontRef<'static> {
+    FontRef::try_from_slice(epaint_default_fonts::UBUNTU_LIGHT).expect("the built-in font is a valid ttf")
+}
+
+//字符的宽度，字体里没有的字符（比如汉字）按一个字号的宽度算
+fn char_width(font: &FontRef, font_size: f32, c: char) -> f32 {
+    let scaled = font.as_scaled(PxScale::from(font_size));
+    match scaled.glyph_id(c) {
+        GlyphId(0) => font_size,
+        id => scaled.h_advance(id),
+    }
+}
+
+//按宽度折行后的每一行文字段
+fn wrap_text(text: &str, font: &FontRef, font_size: f32, width: f32) -> Vec<Vec<TextSpan>> {
+    parse_lines(text).iter().flat_map(|spans| wrap_spans(spans, width, |c| char_width(font, font_size, c))).collect()
+}
+
+//节点文字按画布上的换行宽度折行后的每一行
+fn wrapped_lines(node: &Node, font: &FontRef, font_size: f32) -> Vec<Vec<TextSpan>> {
+    wrap_text(&node.node_text, font, font_size, node.text_wrap_width(&TextLayout::default()))
+}
+
+//导入的节点按文字排版后的大小，和画布上节点跟随文字调整大小一致，导出时文字不会超出节点
+fn fit_text_size(node: &Node, font: &FontRef, font_size: f32) -> Vector {
+    let text_layout = TextLayout::default();
+    let lines = wrap_text(&node.node_text, font, font_size, text_layout.max_width);
+    let width = lines.iter()
+        .map(|spans| spans.iter().flat_map(|span| span.text.chars()).map(|c| char_width(font, font_size, c)).sum::<f32>())
+        .fold(0.0, f32::max);
+    let scaled = font.as_scaled(PxScale::from(font_size));
+    let height = lines.len() as f32 * (scaled.height() + scaled.line_gap());
+    text_layout.node_size(Vector::new(width, height), node.node_shape)
+}
+
+/// 导出图片时四周留白
+pub const EXPORT_PADDING: f32 = 20.0;
+//导出时给边界标题留出的高度
+const BOUNDARY_TITLE_HEIGHT: f32 = 18.0;
+
+//导图在世界坐标下的包围盒（含留白），导出 svg/png 时用来确定画布大小
+pub fn export_bounds(graph: &NodeGraph) -> Rect {
+    //关系连线的曲线在控制点围成的范围内，可能超出节点的包围盒
+    let links = graph.links.keys()
+        .filter_map(|id| graph.link_control_points(id))
+        .flatten()
+        .fold(Rect::NOTHING, |bounds, p| bounds.union(Rect::from_min_max(p, p)));
+    //边界比节点大一圈，上面还有标题
+    let boundaries = graph.boundary_outlines(&|_| true).iter()
+        .map(|(_, outline)| polygon_bounds(outline))
+        .fold(Rect::NOTHING, |bounds, rect| bounds.union(Rect::from_min_max(rect.min - Vector::new(0.0, BOUNDARY_TITLE_HEIGHT), rect.max)));
+    graph.bounds()
+        .map(|bounds| [links, boundaries].into_iter().filter(|rect| !rect.is_negative()).fold(bounds, Rect::union))
+        .unwrap_or_else(|| Rect::from_min_size(Point::ZERO, Vector::ZERO))
+        .expand(EXPORT_PADDING)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //两个根节点，第一个根节点下面有两层子节点，根节点和叶子节点带多行备注
+    fn sample_outlines() -> Vec<Outline> {
+        let mut child = Outline::new("child & <one>");
+        child.label = "because \"quoted\" & <so>\n-- or ---> \\n".to_owned();
+        let mut grandchild = Outline::new("grandchild \"quoted\"");
+        grandchild.note = "a **note** with \"quotes\" & <tags>\n\n- and a list".to_owned();
+        child.children.push(grandchild);
+        let mut first = Outline::new("first root");
+        first.note = "root note\nsecond line".to_owned();
+        first.children.push(child);
+        first.children.push(Outline::new("second child"));
+        let mut second = Outline::new("second root");
+        second.children.push(Outline::new("only child"));
+        vec![first, second]
+    }
+
+    //一条很长的单链
+    fn deep_outline(depth: usize) -> Outline {
+        let mut outline = Outline::new("leaf");
+        for i in 0..depth {
+            let mut father = Outline::new(format!("level {i}"));
+            father.children.push(outline);
+            outline = father;
+        }
+        outline
+    }
+
+    #[test]
+    fn markdown_round_trip() {
+        let outlines = sample_outlines();
+        assert_eq!(markdown::parse(&markdown::write(&outlines)), outlines);
+    }
+
+    #[test]
+    fn opml_round_trip() {
+        let outlines = sample_outlines();
+        assert_eq!(opml::parse(&opml::write(&outlines, "title")).unwrap(), outlines);
+    }
+
+    #[test]
+    fn freemind_round_trip() {
+        //多个根节点时用标题包起来
+        let outlines = sample_outlines();
+        let parsed = freemind::parse(&freemind::write(&outlines, "title")).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].text, "title");
+        assert_eq!(parsed[0].children, outlines);
+        let single = &outlines[..1];
+        assert_eq!(freemind::parse(&freemind::write(single, "title")).unwrap(), single);
+    }
+
+    #[test]
+    fn native_round_trip() {
+        let graph = graph_from_outlines(&sample_outlines());
+        let src = to_native(&graph).unwrap();
+        let loaded = from_native(&src).unwrap();
+        assert_eq!(to_native(&loaded).unwrap(), src);
+        assert_eq!(outlines_from_graph(&loaded), sample_outlines());
+    }
+
+    #[test]
+    fn native_rejects_newer_version() {
+        let src = format!("{{\"version\":{},\"graph\":{{\"nodes\":[],\"last_key\":null}}}}", FORMAT_VERSION + 1);
+        assert!(from_native(&src).is_err());
+    }
+
+    #[test]
+    fn graph_keeps_outline_order() {
+        let graph = graph_from_outlines(&sample_outlines());
+        assert_eq!(graph.nodes.len(), 6);
+        assert!(graph.validate().is_empty());
+        assert_eq!(outlines_from_graph(&graph), sample_outlines());
+    }
+
+    //sample_outlines 的第一个根节点下括起两个子节点的概要，概要节点下面还有一个子节点
+    fn summarized_outlines() -> Vec<Outline> {
+        let mut outlines = sample_outlines();
+        let mut topic = Outline::new("Summary");
+        topic.summary = 2;
+        topic.children.push(Outline::new("topic child"));
+        outlines[0].children.push(topic);
+        outlines
+    }
+
+    #[test]
+    fn summaries_are_exported_after_their_range() {
+        let mut graph = graph_from_outlines(&sample_outlines());
+        let children = graph.ordered_children(graph.root_nodes()[0]);
+        let summary = graph.add_summary(children[0], children[1]).unwrap();
+        let topic = graph.summaries[summary].topic;
+        graph.add_node_with_father_node(topic);
+        let child = graph.query_all_children_nodes(topic)[0];
+        graph.set_text(child, "topic child");
+        assert_eq!(graph.root_nodes().len(), 2);
+        assert_eq!(outlines_from_graph(&graph), summarized_outlines());
+        assert!(!markdown::write(&outlines_from_graph(&graph)).contains("# Summary"));
+    }
+
+    #[test]
+    fn summaries_round_trip() {
+        let outlines = summarized_outlines();
+        assert_eq!(markdown::parse(&markdown::write(&outlines)), outlines);
+        assert_eq!(opml::parse(&opml::write(&outlines, "title")).unwrap(), outlines);
+        assert_eq!(freemind::parse(&freemind::write(&outlines, "title")).unwrap()[0].children, outlines);
+        let graph = graph_from_outlines(&outlines);
+        assert_eq!(graph.summaries.len(), 1);
+        assert!(graph.validate().is_empty());
+        assert_eq!(outlines_from_graph(&graph), outlines);
+        //概要节点在括号外面，它的子节点在它右边
+        let topic = graph.summaries.values().next().unwrap().topic;
+        let child = graph.query_all_children_nodes(topic)[0];
+        assert!(graph.nodes[child].node_pos.x > graph.nodes[topic].node_pos.x);
+    }
+
+    #[test]
+    fn exports_wrap_text_inside_nodes() {
+        let mut root = Outline::new("root");
+        root.children.push(Outline::new("a long line of words ".repeat(10).trim_end()));
+        let graph = graph_from_outlines(&[root]);
+        let font = export_font();
+        for (id, node) in &graph.nodes {
+            let font_size = graph.node_style(id).font_size;
+            let lines = wrapped_lines(node, &font, font_size);
+            assert_eq!(lines.len() > 1, node.node_text.len() > 20);
+            for spans in lines {
+                let width: f32 = spans.iter().flat_map(|span| span.text.chars()).map(|c| char_width(&font, font_size, c)).sum();
+                assert!(width <= node.text_wrap_width(&TextLayout::default()));
+            }
+        }
+        assert!(svg::write(&graph).matches("<tspan x=").count() > 2);
+    }
+
+    #[test]
+    fn deep_outlines_do_not_overflow() {
+        //派生的 PartialEq 也是递归的，这里沿着链逐层比较
+        let depth = 100_000;
+        let graph = graph_from_outlines(&[deep_outline(depth)]);
+        assert_eq!(graph.nodes.len(), depth + 1);
+        let exported = outlines_from_graph(&graph);
+        let parsed = opml::parse(&opml::write(&exported, "deep")).unwrap();
+        let mut outline = &parsed[0];
+        for i in (0..depth).rev() {
+            assert_eq!(outline.text, format!("level {i}"));
+            outline = &outline.children[0];
+        }
+        assert_eq!(outline.text, "leaf");
+    }
+}
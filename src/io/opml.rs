@@ -0,0 +1,42 @@
+//! OPML 大纲，每个节点对应一个 `<outline text="...">`，备注放在常用的 `_note` 属性里，
+//! 到父节点连线上的文字放在 `_connector_label` 属性里，概要节点括起的节点数放在 `_summary` 属性里
+
+use quick_xml::escape::escape;
+use super::Outline;
+
+pub fn write(outlines: &[Outline], title: &str) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n");
+    out.push_str(&format!("  <head>\n    <title>{}</title>\n  </head>\n  <body>\n", escape(title)));
+    //用显式的栈代替递归，Some 是要写出的节点，None 是要写出的结束标签
+    let mut stack: Vec<(Option<&Outline>, usize)> = outlines.iter().rev().map(|outline| (Some(outline), 0)).collect();
+    while let Some((outline, level)) = stack.pop() {
+        let indent = super::xml_indent(level + 2);
+        let Some(outline) = outline else {
+            out.push_str(&format!("{indent}</outline>\n"));
+            continue;
+        };
+        out.push_str(&format!("{indent}<outline text=\"{}\"", escape(outline.text.as_str())));
+        if !outline.note.is_empty() {
+            out.push_str(&format!(" _note=\"{}\"", super::xml_attribute(&outline.note)));
+        }
+        if !outline.label.is_empty() {
+            out.push_str(&format!(" _connector_label=\"{}\"", super::xml_attribute(&outline.label)));
+        }
+        if outline.summary > 0 {
+            out.push_str(&format!(" _summary=\"{}\"", outline.summary));
+        }
+        if outline.children.is_empty() {
+            out.push_str("/>\n");
+        } else {
+            out.push_str(">\n");
+            stack.push((None, level));
+            stack.extend(outline.children.iter().rev().map(|child| (Some(child), level + 1)));
+        }
+    }
+    out.push_str("  </body>\n</opml>\n");
+    out
+}
+
+pub fn parse(src: &str) -> Result<Vec<Outline>, String> {
+    super::freemind::parse_xml_tree(src, b"outline", b"text", true)
+}
@@ -0,0 +1,258 @@
+//! PNG 导出，用 tiny-skia 光栅化，文字用 egui 自带的默认字体绘制
+
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
+use tiny_skia::{FillRule, Paint, PathBuilder, Pixmap, PremultipliedColorU8, Stroke, StrokeDash, Transform};
+use crate::model::node_graph::curve_control_points;
+use crate::model::boundary::polygon_bounds;
+use crate::model::summary::{brace_points, brace_tip};
+use crate::model::connector::{arrow_head, connector_midpoint, connector_points, tapered_outline};
+use crate::model::{Color, ConnectorKind, ConnectorStyle, NodeGraph, NodeShape, Point, Rect, ResolvedStyle, Vector};
+use super::{export_bounds, export_font, wrapped_lines};
+
+//和 `Node::draw` 里的圆角一致
+const ROUNDING: f32 = 5.0;
+//曲线近似成折线时的段数，和画布上一致
+const CURVE_SEGMENTS: usize = 24;
+
+pub fn write(graph: &NodeGraph) -> Result<Vec<u8>, String> {
+    let bounds = export_bounds(graph);
+    let mut pixmap = Pixmap::new(bounds.width().ceil() as u32, bounds.height().ceil() as u32)
+        .ok_or("map is too large to export as png")?;
+    let background = graph.theme.background;
+    pixmap.fill(tiny_skia::Color::from_rgba8(background.r, background.g, background.b, background.a));
+    //世界坐标平移到图片坐标
+    let offset = -bounds.min.to_vector();
+
+    let font = export_font();
+    //边界在最下面
+    for (id, outline) in graph.boundary_outlines(&|_| true) {
+        let Some(boundary) = graph.nodes[id].boundary.as_ref() else { continue };
+        let outline: Vec<Point> = outline.into_iter().map(|p| p + offset).collect();
+        if let Some(path) = polygon(&outline) {
+            if let Some(fill) = boundary.fill {
+                pixmap.fill_path(&path, &paint(fill), FillRule::Winding, Transform::identity(), None);
+            }
+            let stroke = Stroke { width: 1.5, ..Stroke::default() };
+            pixmap.stroke_path(&path, &paint(boundary.color), &stroke, Transform::identity(), None);
+        }
+        if !boundary.title.is_empty() {
+            let bounds = polygon_bounds(&outline);
+            let style = ResolvedStyle { text_color: boundary.color, font_size: 13.0, ..ResolvedStyle::default() };
+            let width: f32 = {
+                let scaled = font.as_scaled(PxScale::from(style.font_size));
+                boundary.title.chars().map(|c| scaled.h_advance(scaled.glyph_id(c))).sum()
+            };
+            let center = Point::new(bounds.min.x + width / 2.0, bounds.min.y - style.font_size * 0.7);
+            draw_text(&mut pixmap, &font, &boundary.title, center, &style);
+        }
+    }
+
+    //概要的括号和到概要节点的连线
+    for (id, summary) in &graph.summaries {
+        let (Some((bounds, side)), Some(topic)) = (graph.summary_brace(id, &|_| true), graph.nodes.get(summary.topic)) else { continue };
+        let connector = graph.connector_style(summary.from);
+        let rect = topic.rect();
+        let edge = Point::new(if side > 0.0 { rect.min.x } else { rect.max.x }, rect.center().y);
+        let mut pb = PathBuilder::new();
+        for (i, p) in brace_points(bounds, side).into_iter().map(|p| p + offset).enumerate() {
+            if i == 0 { pb.move_to(p.x, p.y) } else { pb.line_to(p.x, p.y) }
+        }
+        let (tip, edge) = (brace_tip(bounds, side) + offset, edge + offset);
+        pb.move_to(tip.x, tip.y);
+        pb.line_to(edge.x, edge.y);
+        if let Some(path) = pb.finish() {
+            let stroke = Stroke { width: connector.width, ..Stroke::default() };
+            pixmap.stroke_path(&path, &paint(connector.color), &stroke, Transform::identity(), None);
+        }
+    }
+
+    for (id, node) in &graph.nodes {
+        if let Some(father) = node.father_id.and_then(|id| graph.nodes.get(id)) {
+            let connector = graph.connector_style(id);
+            let control = curve_control_points(node, father).map(|p| p + offset);
+            let depth = graph.node_depth(id);
+            draw_connector(&mut pixmap, &connector, control, (connector.width_at(depth), connector.width_at(depth - 1)));
+        }
+    }
+
+    for (id, node) in &graph.nodes {
+        if node.connector_label.is_empty() {
+            continue;
+        }
+        let Some(father) = node.father_id.and_then(|id| graph.nodes.get(id)) else { continue };
+        let connector = graph.connector_style(id);
+        let center = connector_midpoint(connector.kind, curve_control_points(node, father).map(|p| p + offset));
+        draw_label(&mut pixmap, &font, &node.connector_label, center, connector.color, background);
+    }
+    for (id, node) in &graph.nodes {
+        let rect = Rect::from_center_size(node.node_pos + offset, node.node_size);
+        let style = graph.node_style(id);
+        draw_shape(&mut pixmap, node.node_shape, rect, &style);
+        //图片里只有一种字体，去掉 Markdown 标记只画文字，按画布上的宽度换行
+        let text: Vec<String> = wrapped_lines(node, &font, style.font_size).iter()
+            .map(|spans| spans.iter().map(|span| span.text.as_str()).collect())
+            .collect();
+        draw_text(&mut pixmap, &font, &text.join("\n"), rect.center(), &style);
+    }
+    //关系连线画在节点上面，和画布上一致
+    for (id, link) in &graph.links {
+        let Some(control) = graph.link_control_points(id) else { continue };
+        let style = &link.style;
+        let control = control.map(|p| p + offset);
+        draw_connector(&mut pixmap, style, control, (style.width, style.width * 3.0));
+        if !link.label.is_empty() {
+            draw_label(&mut pixmap, &font, &link.label, connector_midpoint(style.kind, control), style.color, background);
+        }
+    }
+    pixmap.encode_png().map_err(|e| format!("failed to encode png: {e}"))
+}
+
+fn paint(color: Color) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(color.r, color.g, color.b, color.a);
+    paint.anti_alias = true;
+    paint
+}
+
+//一条连线和它的箭头，和 `paint_connector` 一致，tapered_widths 是 Tapered 时两端的线宽
+fn draw_connector(pixmap: &mut Pixmap, connector: &ConnectorStyle, control: [Point; 4], tapered_widths: (f32, f32)) {
+    let color = paint(connector.color);
+    let points = connector_points(connector.kind, control, CURVE_SEGMENTS);
+    if connector.kind == ConnectorKind::Tapered && !connector.dashed {
+        let outline = tapered_outline(&points, tapered_widths.0, tapered_widths.1);
+        if let Some(path) = polygon(&outline) {
+            pixmap.fill_path(&path, &color, FillRule::Winding, Transform::identity(), None);
+        }
+    } else {
+        let mut pb = PathBuilder::new();
+        if connector.kind == ConnectorKind::Bezier {
+            let [p0, p1, p2, p3] = control;
+            pb.move_to(p0.x, p0.y);
+            pb.cubic_to(p1.x, p1.y, p2.x, p2.y, p3.x, p3.y);
+        } else {
+            pb.move_to(points[0].x, points[0].y);
+            for p in &points[1..] {
+                pb.line_to(p.x, p.y);
+            }
+        }
+        let dash = connector.dashed.then(|| connector.dash_pattern()).and_then(|(dash, gap)| StrokeDash::new(vec![dash, gap], 0.0));
+        let stroke = Stroke { width: connector.width, dash, ..Stroke::default() };
+        if let Some(path) = pb.finish() {
+            pixmap.stroke_path(&path, &color, &stroke, Transform::identity(), None);
+        }
+    }
+    if connector.arrow {
+        if let Some(path) = arrow_head(points[0], points[1], connector.arrow_size()).and_then(|head| polygon(&head)) {
+            pixmap.fill_path(&path, &color, FillRule::Winding, Transform::identity(), None);
+        }
+    }
+}
+
+fn polygon(points: &[Point]) -> Option<tiny_skia::Path> {
+    let (first, rest) = points.split_first()?;
+    let mut pb = PathBuilder::new();
+    pb.move_to(first.x, first.y);
+    for p in rest {
+        pb.line_to(p.x, p.y);
+    }
+    pb.close();
+    pb.finish()
+}
+
+//节点的外形，和 `Node::draw` 里的 paint_shape 一致
+fn draw_shape(pixmap: &mut Pixmap, shape: NodeShape, rect: Rect, style: &ResolvedStyle) {
+    let border = (style.border_width > 0.0).then(|| Stroke { width: style.border_width, ..Stroke::default() });
+    let path = match shape {
+        NodeShape::RoundedRect => rounded_rect(rect, ROUNDING),
+        NodeShape::Capsule => rounded_rect(rect, rect.height().min(rect.width()) / 2.0),
+        NodeShape::Ellipse | NodeShape::Diamond => polygon(&shape.outline(rect, 64)),
+        NodeShape::Underline => {
+            let mut pb = PathBuilder::new();
+            pb.move_to(rect.min.x, rect.max.y);
+            pb.line_to(rect.max.x, rect.max.y);
+            if let Some(path) = pb.finish() {
+                let (color, stroke) = match border {
+                    Some(stroke) => (style.border_color, stroke),
+                    None => (style.fill, Stroke { width: 2.0, ..Stroke::default() }),
+                };
+                pixmap.stroke_path(&path, &paint(color), &stroke, Transform::identity(), None);
+            }
+            return;
+        }
+    };
+    if let Some(path) = path {
+        pixmap.fill_path(&path, &paint(style.fill), FillRule::Winding, Transform::identity(), None);
+        if let Some(stroke) = border {
+            pixmap.stroke_path(&path, &paint(style.border_color), &stroke, Transform::identity(), None);
+        }
+    }
+}
+
+fn rounded_rect(rect: Rect, r: f32) -> Option<tiny_skia::Path> {
+    let r = r.min(rect.width() / 2.0).min(rect.height() / 2.0);
+    let (min, max) = (rect.min, rect.max);
+    let mut pb = PathBuilder::new();
+    pb.move_to(min.x + r, min.y);
+    pb.line_to(max.x - r, min.y);
+    pb.quad_to(max.x, min.y, max.x, min.y + r);
+    pb.line_to(max.x, max.y - r);
+    pb.quad_to(max.x, max.y, max.x - r, max.y);
+    pb.line_to(min.x + r, max.y);
+    pb.quad_to(min.x, max.y, min.x, max.y - r);
+    pb.line_to(min.x, min.y + r);
+    pb.quad_to(min.x, min.y, min.x + r, min.y);
+    pb.close();
+    pb.finish()
+}
+
+//连线中间的文字，底下垫一块背景色，盖住文字下面的线
+fn draw_label(pixmap: &mut Pixmap, font: &FontRef, text: &str, center: Point, color: Color, background: Color) {
+    let style = ResolvedStyle { text_color: color, font_size: 12.0, ..ResolvedStyle::default() };
+    let scaled = font.as_scaled(PxScale::from(style.font_size));
+    let width: f32 = text.chars().map(|c| scaled.h_advance(scaled.glyph_id(c))).sum();
+    let size = Vector::new(width + 4.0, scaled.height() + 4.0);
+    if let Some(rect) = tiny_skia::Rect::from_xywh(center.x - size.x / 2.0, center.y - size.y / 2.0, size.x, size.y) {
+        pixmap.fill_rect(rect, &paint(background), Transform::identity(), None);
+    }
+    draw_text(pixmap, font, text, center, &style);
+}
+
+//多行文字以 center 为中心绘制，直接把字形覆盖率混合进像素；默认字体只有一种字重，粗体和斜体不区分
+fn draw_text(pixmap: &mut Pixmap, font: &FontRef, text: &str, center: Point, style: &ResolvedStyle) {
+    let color = style.text_color;
+    let font = font.as_scaled(PxScale::from(style.font_size));
+    let line_height = font.height() + font.line_gap();
+    let lines: Vec<&str> = text.lines().collect();
+    let mut baseline_y = center.y - line_height * lines.len() as f32 / 2.0 + font.ascent();
+    let (width, height) = (pixmap.width() as i32, pixmap.height() as i32);
+    for line in lines {
+        let line_width: f32 = line.chars().map(|c| font.h_advance(font.glyph_id(c))).sum();
+        let mut caret = Point::new(center.x - line_width / 2.0, baseline_y);
+        for c in line.chars() {
+            let mut glyph = font.scaled_glyph(c);
+            glyph.position = ab_glyph::point(caret.x, caret.y);
+            caret += Vector::new(font.h_advance(glyph.id), 0.0);
+            let Some(outlined) = font.outline_glyph(glyph) else { continue };
+            let glyph_bounds = outlined.px_bounds();
+            let pixels = pixmap.pixels_mut();
+            outlined.draw(|x, y, coverage| {
+                let px = glyph_bounds.min.x as i32 + x as i32;
+                let py = glyph_bounds.min.y as i32 + y as i32;
+                if px < 0 || py < 0 || px >= width || py >= height {
+                    return;
+                }
+                let pixel = &mut pixels[(py * width + px) as usize];
+                let blend = |bg: u8, fg: u8| (bg as f32 + (fg as f32 - bg as f32) * coverage.min(1.0)) as u8;
+                if let Some(blended) = PremultipliedColorU8::from_rgba(
+                    blend(pixel.red(), color.r),
+                    blend(pixel.green(), color.g),
+                    blend(pixel.blue(), color.b),
+                    255,
+                ) {
+                    *pixel = blended;
+                }
+            });
+        }
+        baseline_y += line_height;
+    }
+}
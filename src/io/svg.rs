@@ -0,0 +1,244 @@
+//! SVG 导出，节点、文字和连接曲线与画布上的绘制保持一致
+
+use quick_xml::escape::escape;
+use crate::model::node_graph::curve_control_points;
+use crate::model::boundary::polygon_bounds;
+use crate::model::summary::{brace_points, brace_tip};
+use crate::model::connector::{arrow_head, connector_midpoint, connector_points, tapered_outline};
+use crate::model::{Color, ConnectorKind, ConnectorStyle, Node, NodeGraph, NodeShape, Point, ResolvedStyle, TextSpan};
+use super::{export_bounds, export_font, wrapped_lines};
+
+pub fn write(graph: &NodeGraph) -> String {
+    let bounds = export_bounds(graph);
+    let font = export_font();
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"{x} {y} {w} {h}\">\n",
+        x = bounds.min.x,
+        y = bounds.min.y,
+        w = bounds.width(),
+        h = bounds.height(),
+    );
+    out.push_str(&format!(
+        "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+        bounds.min.x,
+        bounds.min.y,
+        bounds.width(),
+        bounds.height(),
+        graph.theme.background.to_hex()
+    ));
+    //边界在最下面
+    for (id, outline) in graph.boundary_outlines(&|_| true) {
+        let Some(boundary) = graph.nodes[id].boundary.as_ref() else { continue };
+        let points: Vec<String> = outline.iter().copied().map(point).collect();
+        let fill = boundary.fill.map_or("none".to_owned(), |fill| fill.to_hex());
+        let opacity = boundary.fill.map_or(String::new(), |fill| format!(" fill-opacity=\"{}\"", fill.a as f32 / 255.0));
+        out.push_str(&format!(
+            "  <polygon points=\"{}\" fill=\"{fill}\"{opacity} stroke=\"{}\" stroke-width=\"1.5\"/>\n",
+            points.join(" "),
+            boundary.color.to_hex()
+        ));
+        if !boundary.title.is_empty() {
+            let bounds = polygon_bounds(&outline);
+            out.push_str(&format!(
+                "  <text x=\"{}\" y=\"{}\" font-size=\"13\" fill=\"{}\">{}</text>\n",
+                bounds.min.x,
+                bounds.min.y - 4.0,
+                boundary.color.to_hex(),
+                escape(boundary.title.as_str())
+            ));
+        }
+    }
+    //概要的括号和到概要节点的连线
+    for (id, summary) in &graph.summaries {
+        let (Some((bounds, side)), Some(topic)) = (graph.summary_brace(id, &|_| true), graph.nodes.get(summary.topic)) else { continue };
+        let connector = graph.connector_style(summary.from);
+        let points: Vec<String> = brace_points(bounds, side).into_iter().map(point).collect();
+        let rect = topic.rect();
+        let edge = Point::new(if side > 0.0 { rect.min.x } else { rect.max.x }, rect.center().y);
+        out.push_str(&format!(
+            "  <path d=\"M {} M {} L {}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"/>\n",
+            points.join(" L "),
+            point(brace_tip(bounds, side)),
+            point(edge),
+            connector.color.to_hex(),
+            connector.width
+        ));
+    }
+    //先画曲线，节点盖在曲线上面
+    for (id, node) in &graph.nodes {
+        if let Some(father) = node.father_id.and_then(|id| graph.nodes.get(id)) {
+            let connector = graph.connector_style(id);
+            let control = curve_control_points(node, father);
+            let depth = graph.node_depth(id);
+            out.push_str(&connector_path(&connector, control, (connector.width_at(depth), connector.width_at(depth - 1))));
+            if !node.connector_label.is_empty() {
+                let center = connector_midpoint(connector.kind, control);
+                out.push_str(&label(center, &node.connector_label, connector.color, graph.theme.background));
+            }
+        }
+    }
+    for (id, node) in &graph.nodes {
+        let style = graph.node_style(id);
+        out.push_str(&shape(node, &style));
+        let font_size = style.font_size;
+        let lines = wrapped_lines(node, &font, font_size);
+        let first_dy = -(lines.len().saturating_sub(1) as f32) * font_size * 0.6;
+        out.push_str(&format!(
+            "  <text x=\"{}\" y=\"{}\" font-size=\"{font_size}\"{}{} text-anchor=\"middle\" dominant-baseline=\"central\" fill=\"{}\">",
+            node.node_pos.x,
+            node.node_pos.y,
+            if style.bold { " font-weight=\"bold\"" } else { "" },
+            if style.italic { " font-style=\"italic\"" } else { "" },
+            style.text_color.to_hex()
+        ));
+        for (i, line) in lines.iter().enumerate() {
+            let dy = if i == 0 { first_dy } else { font_size * 1.2 };
+            out.push_str(&format!("<tspan x=\"{}\" dy=\"{dy}\">", node.node_pos.x));
+            for span in line {
+                out.push_str(&text_span(span));
+            }
+            out.push_str("</tspan>");
+        }
+        out.push_str("</text>\n");
+    }
+    //关系连线画在节点上面，和画布上一致
+    for (id, link) in &graph.links {
+        let Some(control) = graph.link_control_points(id) else { continue };
+        let style = &link.style;
+        out.push_str(&connector_path(style, control, (style.width, style.width * 3.0)));
+        if !link.label.is_empty() {
+            out.push_str(&label(connector_midpoint(style.kind, control), &link.label, style.color, graph.theme.background));
+        }
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+//节点文字里链接的颜色，和画布上一致
+const LINK_COLOR: Color = Color::from_rgb(40, 110, 220);
+
+//曲线近似成折线时的段数，和画布上一致
+const CURVE_SEGMENTS: usize = 24;
+
+//一条连线和它的箭头，和 `paint_connector` 一致，tapered_widths 是 Tapered 时两端的线宽
+fn connector_path(connector: &ConnectorStyle, control: [Point; 4], tapered_widths: (f32, f32)) -> String {
+    let color = connector.color.to_hex();
+    let dash = if connector.dashed {
+        let (dash, gap) = connector.dash_pattern();
+        format!(" stroke-dasharray=\"{dash} {gap}\"")
+    } else {
+        String::new()
+    };
+    let points = connector_points(connector.kind, control, CURVE_SEGMENTS);
+    let arrow = match arrow_head(points[0], points[1], connector.arrow_size()).filter(|_| connector.arrow) {
+        Some(head) => {
+            let head: Vec<String> = head.into_iter().map(point).collect();
+            format!("  <polygon points=\"{}\" fill=\"{color}\"/>\n", head.join(" "))
+        }
+        None => String::new(),
+    };
+    let d = match connector.kind {
+        ConnectorKind::Tapered if !connector.dashed => {
+            let outline: Vec<String> = tapered_outline(&points, tapered_widths.0, tapered_widths.1)
+                .into_iter()
+                .map(point)
+                .collect();
+            return format!("  <polygon points=\"{}\" fill=\"{color}\"/>\n{arrow}", outline.join(" "));
+        }
+        ConnectorKind::Bezier => {
+            let [p0, p1, p2, p3] = control;
+            format!("M {} C {} {} {}", point(p0), point(p1), point(p2), point(p3))
+        }
+        _ => {
+            let points: Vec<String> = points.into_iter().map(point).collect();
+            format!("M {}", points.join(" L "))
+        }
+    };
+    format!("  <path d=\"{d}\" fill=\"none\" stroke=\"{color}\" stroke-width=\"{}\"{dash}/>\n{arrow}", connector.width)
+}
+
+//连线中间的文字，用背景色描边盖住下面的线
+fn label(center: Point, text: &str, color: Color, background: Color) -> String {
+    format!(
+        "  <text x=\"{}\" y=\"{}\" font-size=\"12\" text-anchor=\"middle\" dominant-baseline=\"central\" fill=\"{}\" stroke=\"{}\" stroke-width=\"3\" paint-order=\"stroke\">{}</text>\n",
+        center.x,
+        center.y,
+        color.to_hex(),
+        background.to_hex(),
+        escape(text)
+    )
+}
+
+//节点文字里带 Markdown 格式的一段，和画布上的 text_job 一致
+fn text_span(span: &TextSpan) -> String {
+    let mut attributes = String::new();
+    if span.bold {
+        attributes.push_str(" font-weight=\"bold\"");
+    }
+    if span.italic {
+        attributes.push_str(" font-style=\"italic\"");
+    }
+    if span.code {
+        attributes.push_str(" font-family=\"monospace\"");
+    }
+    let decoration: Vec<&str> = [(span.strikethrough, "line-through"), (span.link.is_some(), "underline")]
+        .into_iter()
+        .filter_map(|(on, name)| on.then_some(name))
+        .collect();
+    if !decoration.is_empty() {
+        attributes.push_str(&format!(" text-decoration=\"{}\"", decoration.join(" ")));
+    }
+    if span.link.is_some() {
+        attributes.push_str(&format!(" fill=\"{}\"", LINK_COLOR.to_hex()));
+    }
+    let text = escape(span.text.as_str());
+    let tspan = if attributes.is_empty() { text.into_owned() } else { format!("<tspan{attributes}>{text}</tspan>") };
+    match &span.link {
+        Some(url) => format!("<a href=\"{}\">{tspan}</a>", escape(url.as_str())),
+        None => tspan,
+    }
+}
+
+//节点的外形，和 `Node::draw` 里的 paint_shape 一致
+fn shape(node: &Node, style: &ResolvedStyle) -> String {
+    let rect = node.rect();
+    let fill = style.fill.to_hex();
+    //边框属性，宽度为 0 时不画
+    let border = if style.border_width > 0.0 {
+        format!(" stroke=\"{}\" stroke-width=\"{}\"", style.border_color.to_hex(), style.border_width)
+    } else {
+        String::new()
+    };
+    let (w, h) = (rect.width(), rect.height());
+    match node.node_shape {
+        NodeShape::RoundedRect | NodeShape::Capsule => {
+            let rx = if node.node_shape == NodeShape::Capsule { h.min(w) / 2.0 } else { 5.0 };
+            format!(
+                "  <rect x=\"{}\" y=\"{}\" width=\"{w}\" height=\"{h}\" rx=\"{rx}\" fill=\"{fill}\"{border}/>\n",
+                rect.min.x, rect.min.y
+            )
+        }
+        NodeShape::Ellipse => format!(
+            "  <ellipse cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\" fill=\"{fill}\"{border}/>\n",
+            node.node_pos.x,
+            node.node_pos.y,
+            w / 2.0,
+            h / 2.0
+        ),
+        NodeShape::Diamond => {
+            let points: Vec<String> = node.node_shape.outline(rect, 4).into_iter().map(point).collect();
+            format!("  <polygon points=\"{}\" fill=\"{fill}\"{border}/>\n", points.join(" "))
+        }
+        NodeShape::Underline => {
+            let (color, width) = if style.border_width > 0.0 { (style.border_color.to_hex(), style.border_width) } else { (fill, 2.0) };
+            format!(
+                "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{color}\" stroke-width=\"{width}\"/>\n",
+                rect.min.x, rect.max.y, rect.max.x, rect.max.y
+            )
+        }
+    }
+}
+
+fn point(p: Point) -> String {
+    format!("{} {}", p.x, p.y)
+}
@@ -0,0 +1,8 @@
+pub mod model;
+pub mod io;
+#[cfg(feature = "gui")]
+pub mod view;
+
+pub use model::{Node, NodeGraph, NodeId};
+#[cfg(feature = "gui")]
+pub use view::{GraphState, MindMap, MindMapEvent, MindMapResponse, Minimap, PanZoom, ViewSettings};
@@ -1,168 +1,76 @@
-mod canvas;
-mod node;
-mod pan_zoom;
-mod node_graph;
-mod traits;
-mod handlers;
-
-use std::thread;
-use std::thread::spawn;
-use crate::pan_zoom::{PanZoom};
-use egui::{Align2, Id, Layout, menu, Order, Painter, Rounding, Sense, TextStyle, TopBottomPanel, Widget, Window, FontData, FontFamily};
-use egui::{Ui, Response, Vec2, pos2, Color32};
-use crate::handlers::*;
-/// 自定义按钮组件
-fn custom_button(ui: &mut Ui, text: &str) -> Response {
-    let desired_size = Vec2::new(100.0, 30.0); // 按钮大小
-    let (rect, response) = ui.allocate_exact_size(desired_size, egui::Sense::click());
-    if ui.is_rect_visible(rect) {
-        // 绘制按钮
-        let visuals = ui.style().interact(&response);
-        ui.painter().rect_filled(rect, visuals.rounding, visuals.bg_fill);
-        ui.painter().text(
-            pos2(rect.center().x, rect.center().y),
-            egui::Align2::CENTER_CENTER,
-            text,
-            egui::TextStyle::resolve(&TextStyle::Small, &Default::default()),
-            visuals.text_color(),
-        );
-    }
-    response
-}
-
-fn circle_interactable(ui: &mut egui::Ui, center: &mut Pos2, radius: f32, id: Id) -> Response {
-    // 计算圆覆盖的矩形区域
-    let rect = egui::Rect::from_center_size(*center, vec2(radius * 2.0, radius * 2.0));
-
-    // 使用一个透明按钮覆盖在圆上
-    let response = ui.allocate_rect(rect, Sense::click_and_drag()).interact(Sense::click_and_drag());
-
-    if response.dragged() {
-        // 如果按钮被拖动，更新圆心位置
-        *center += response.drag_delta();
-    }
-
-    // 绘制圆
-    ui.painter().circle_filled(*center, radius, Color32::from_rgb(150, 150, 250));
-// 绘制框
-    ui.painter().rect_stroke(rect,Rounding::default(), ui.ctx().style().visuals.window_stroke);
-    response
-}
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use eframe::App;
 use eframe::egui::{self, CentralPanel, Context};
-use eframe::emath::TSTransform;
-use eframe::epaint::{Pos2, Rect, Stroke, vec2};
 use eframe::epaint::text::FontDefinitions;
-use egui::epaint::text::layout;
+use egui::{menu, Align2, FontData, FontFamily, Ui, Window};
 use egui::Order::Foreground;
-use crate::canvas::{Canvas};
-use crate::node::{ButtonState, Node, NodeState};
-use crate::node_graph::{GraphResponse, GraphState, NodeGraph, NodeId};
-use crate::traits::{NodeGraphs, View};
-
+use egui_app::{GraphState, MindMap, Minimap, NodeGraph, PanZoom, ViewSettings};
+use egui_app::model::{Alignment, Axis, Boundary, ConnectorKind, ConnectorStyle, Hyperlink, NodeId, NodeShape, Theme};
+use egui_app::view::boundary::boundary_panel;
+use egui_app::view::cross_link::link_panel;
+use egui_app::view::hyperlink::{hyperlink_panel, link_targets};
+use egui_app::view::note::{note_panel, search_results};
+use egui_app::{io, MindMapEvent};
+use egui_app::view::style_panel::style_panel;
+
+//演示程序：菜单栏 + 占满中间区域的思维导图控件
+#[derive(Default)]
 pub struct MyApp{
-    canvas: Canvas,
     node_graph: NodeGraph,
-    circle_center:Pos2,
     pan_zoom: PanZoom,
+    //网格、参考线等显示设置
+    settings: ViewSettings,
     window_state:bool,
     graph_state: GraphState,
-    graph_response: GraphResponse,
+    hide_minimap: bool,
+    //搜索框里的文字，不为空时在左侧列出搜索结果
+    search: String,
+    //当前导图的文件，打开、保存或从链接打开其它导图后才有
+    file: Option<PathBuf>,
+    //当前导图所在的目录，链接里的相对路径从这里开始
+    base_dir: PathBuf,
+    //上次打开或保存时导图的原生格式内容，和现在的内容比较判断有没有没保存的修改
+    saved: Option<String>,
+    //正在输入路径的打开或另存为对话框
+    file_dialog: Option<FileDialog>,
+    //超链接指向的其它导图里的节点，按文件缓存，编辑链接时列出来选择
+    linked_maps: HashMap<PathBuf, LinkedMap>,
+    //已经读出来、等用户确认后替换当前导图的链接导图
+    pending_link: Option<PendingLink>,
+    //打开、保存文件或链接导图失败的原因
+    error: Option<String>,
 }
-impl Default for MyApp{
-    fn default() -> Self {
-        Self{
-            canvas:Canvas::default(),
-            node_graph:NodeGraph::default(),
-            circle_center:Pos2::default(),
-            pan_zoom:PanZoom::default(),
-            window_state:false,
-            graph_state:GraphState::default(),
-            graph_response:GraphResponse::default()
-        }
-    }
-}
-
-
-impl  MyApp{
 
-    //整个应用的缩放和平移
-    pub fn pan_zoom( &mut self,ui: &mut Ui,){
-        let (id, rect) = ui.allocate_space(ui.available_size());
-        let mut response = ui.interact(rect, id, egui::Sense::click_and_drag());
-
-
-        ui.painter().rect_stroke(rect,Rounding::default(), ui.ctx().style().visuals.window_stroke);
-
-        if response.dragged() {
-            self.pan_zoom.transform.translation += response.drag_delta();
-
-        }
-
-        if let Some(pointer)=ui.ctx().input(|i| { i.pointer.hover_pos()})
-        {
-          //  println!("hover is {:?}",pointer);
-            if response.hovered() {
-                let pointer_in_layer = self.pan_zoom.transform.inverse()*pointer;
-                let zoom_delta = ui.ctx().input(|i| i.zoom_delta());
-                let pan_delta = ui.ctx().input(|i| i.smooth_scroll_delta);
-                //println!("zoom is {zoom_delta:?}");
-
-// Zoom in on pointer:
-                self.pan_zoom.transform = self.pan_zoom.transform
-                    * TSTransform::from_translation(pointer_in_layer.to_vec2())
-                    * TSTransform::from_scaling(zoom_delta)
-                    * TSTransform::from_translation(-pointer_in_layer.to_vec2());
-
-
-            }
-        }
-        if response.double_clicked(){
-
-            let Some(pos)= ui.ctx().pointer_hover_pos() else { todo!() };
-            //这里要 inverse transform  因为node draw时 要用加 pan ，这里的pos 要还原成 原始左边，不然绘制会加2遍 transform
-            self.add_node(Some(self.pan_zoom.transform.inverse()*pos ),None);
-           //   println!("double_clicked is {:?}",pos);
-        }
+//打开或另存为对话框：要做的操作、输入的路径，以及打开时当前导图有没有没保存的修改
+struct FileDialog {
+    save: bool,
+    path: String,
+    dirty: bool,
+}
 
-    }
+//等用户确认的链接导图：文件、读出的导图、链接的目标节点，以及当前导图有没有没保存的修改
+struct PendingLink {
+    path: PathBuf,
+    graph: NodeGraph,
+    node: NodeId,
+    dirty: bool,
+}
 
-    pub fn add_node(&mut self, pos2:Option<Pos2>,father_node_id:Option<NodeId>){
-            match pos2 {
-                None => {}
-                Some(pos) => {
-                   let mut node_id= self.node_graph.add_node_with_position(pos);
-                    //这里创建完node 之后，还要创建node state
-                    self.graph_state.node_state.insert_with_key(|mut node_id: NodeId|{
-                        node_id=node_id;
-                        NodeState::UnSelected
-                    });
-                    self.graph_state.graph_button_state.insert_with_key(|mut k| {
-                        k = node_id;
-                        ButtonState::UnFold
-                    }); // 插入新的节点状态
-                }
-            }
-            match father_node_id {
-                None => {}
-                Some(id) => {
-                    let mut node_id=self.node_graph.add_node_with_father_node(id);
-                    //这里创建完node 之后，还要创建node state
-                    self.graph_state.node_state.insert_with_key(|mut node_id: NodeId|{
-                        node_id=node_id;
-                        NodeState::UnSelected
-                    });
-                    self.graph_state.graph_button_state.insert_with_key(|mut k| {
-                        k = node_id;
-                        ButtonState::UnFold
-                    }); // 插入新的节点状态
-                }
-            }
-    }
+//缓存的链接导图：读取时文件的修改时间，文件改过以后重新读取
+struct LinkedMap {
+    modified: Option<SystemTime>,
+    targets: Result<Vec<(NodeId, String)>, String>,
 }
 
 impl App for MyApp {
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        //界面的深浅色跟着导图的主题走
+        if ctx.style().visuals.dark_mode != self.node_graph.theme.dark {
+            ctx.set_visuals(if self.node_graph.theme.dark { egui::Visuals::dark() } else { egui::Visuals::light() });
+        }
 
 
         egui::TopBottomPanel::top("my_top_panel").show(ctx, |ui| {
@@ -171,48 +79,398 @@ impl App for MyApp {
                     if ui.button("new file").clicked() {
                         self.window_state=true;
                     }
-
+                    if ui.button("Open...").clicked() {
+                        self.file_dialog = Some(self.file_dialog(false));
+                        ui.close_menu();
+                    }
+                    if ui.button("Save").clicked() {
+                        match self.file.clone() {
+                            Some(file) => {
+                                self.save(file);
+                            }
+                            None => self.file_dialog = Some(self.file_dialog(true)),
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button("Save as...").clicked() {
+                        self.file_dialog = Some(self.file_dialog(true));
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("View", |ui| {
+                    if ui.button("Zoom to fit (Shift+1)").clicked() {
+                        self.pan_zoom.zoom_to_fit(&self.node_graph);
+                        ui.close_menu();
+                    }
+                    if ui.button("Zoom to selection (Shift+2)").clicked() {
+                        self.pan_zoom.zoom_to_selection(&self.node_graph, &self.graph_state);
+                        ui.close_menu();
+                    }
+                    if ui.button("Zoom to 100% (0)").clicked() {
+                        self.pan_zoom.reset_zoom();
+                        ui.close_menu();
+                    }
+                    if ui.button("Zoom in (+)").clicked() {
+                        self.pan_zoom.zoom_in();
+                    }
+                    if ui.button("Zoom out (-)").clicked() {
+                        self.pan_zoom.zoom_out();
+                    }
+                    ui.separator();
+                    ui.menu_button("Theme", |ui| {
+                        for theme in Theme::builtin() {
+                            let current = self.node_graph.theme.name == theme.name;
+                            if ui.radio(current, theme.name.as_str()).clicked() {
+                                //切换主题时保留分支颜色开关和连线样式
+                                let branch_colors = self.node_graph.theme.branch_colors;
+                                let ConnectorStyle { kind, arrow, dashed, .. } = self.node_graph.theme.connector;
+                                let connector = ConnectorStyle { kind, arrow, dashed, ..theme.connector };
+                                self.node_graph.theme = Theme { branch_colors, connector, ..theme };
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    ui.checkbox(&mut self.node_graph.theme.branch_colors, "Branch colors");
+                    ui.menu_button("Connectors", |ui| {
+                        let connector = &mut self.node_graph.theme.connector;
+                        for kind in ConnectorKind::ALL {
+                            ui.radio_value(&mut connector.kind, kind, kind.name());
+                        }
+                        ui.separator();
+                        ui.checkbox(&mut connector.arrow, "Arrowheads");
+                        ui.checkbox(&mut connector.dashed, "Dashed");
+                    });
+                    ui.checkbox(&mut self.settings.grid.visible, "Grid");
+                    ui.checkbox(&mut self.settings.grid.snap, "Snap to grid");
+                    let mut show_minimap = !self.hide_minimap;
+                    if ui.checkbox(&mut show_minimap, "Minimap").changed() {
+                        self.hide_minimap = !show_minimap;
+                    }
+                });
+                ui.menu_button("Arrange", |ui| {
+                    let selected = self.graph_state.selected_nodes();
+                    ui.add_enabled_ui(selected.len() >= 2, |ui| {
+                        for (label, alignment) in [
+                            ("Align left", Alignment::Left),
+                            ("Align right", Alignment::Right),
+                            ("Align top", Alignment::Top),
+                            ("Align bottom", Alignment::Bottom),
+                            ("Align centers horizontally", Alignment::CenterHorizontal),
+                            ("Align centers vertically", Alignment::CenterVertical),
+                        ] {
+                            if ui.button(label).clicked() {
+                                self.node_graph.align_nodes(&selected, alignment);
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    ui.separator();
+                    ui.add_enabled_ui(selected.len() >= 3, |ui| {
+                        if ui.button("Distribute horizontally").clicked() {
+                            self.node_graph.distribute_nodes(&selected, Axis::Horizontal);
+                            ui.close_menu();
+                        }
+                        if ui.button("Distribute vertically").clicked() {
+                            self.node_graph.distribute_nodes(&selected, Axis::Vertical);
+                            ui.close_menu();
+                        }
+                    });
+                    ui.separator();
+                    ui.add_enabled_ui(!selected.is_empty(), |ui| {
+                        ui.menu_button("Shape", |ui| {
+                            for shape in NodeShape::ALL {
+                                if ui.button(shape.name()).clicked() {
+                                    for &id in &selected {
+                                        self.node_graph.set_shape(id, shape);
+                                    }
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                    });
+                    ui.add_enabled_ui(!selected.is_empty(), |ui| {
+                        if ui.button("Add boundary").clicked() {
+                            for &id in &selected {
+                                self.node_graph.set_boundary(id, Some(Boundary::default()));
+                            }
+                            self.graph_state.selected_boundary = selected.first().copied();
+                            ui.close_menu();
+                        }
+                        if ui.button("Remove boundary").clicked() {
+                            for &id in &selected {
+                                self.node_graph.set_boundary(id, None);
+                            }
+                            ui.close_menu();
+                        }
+                    });
+                    //同一个父节点下选中的几个节点加一个概要，括号从排在最前面的选中节点括到最后面的
+                    let father = selected.first().and_then(|id| self.node_graph.nodes[*id].father_id);
+                    let siblings = father.filter(|_| selected.iter().all(|id| self.node_graph.nodes[*id].father_id == father));
+                    if ui.add_enabled(siblings.is_some(), egui::Button::new("Add summary")).clicked() {
+                        if let Some(father) = siblings {
+                            let range: Vec<_> = self.node_graph.children(father).iter().filter(|id| selected.contains(id)).copied().collect();
+                            if let (Some(&from), Some(&to)) = (range.first(), range.last()) {
+                                self.node_graph.add_summary(from, to);
+                            }
+                        }
+                        ui.close_menu();
+                    }
+                    //两个节点之间加一条关系连线，从先选中的节点（id 小的）连到另一个
+                    if ui.add_enabled(selected.len() == 2, egui::Button::new("Link nodes")).clicked() {
+                        self.graph_state.selected_link = self.node_graph.add_link(selected[0], selected[1]);
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    ui.checkbox(&mut self.settings.smart_guides, "Smart guides");
+                });
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.search).hint_text("Search").desired_width(160.0));
                 });
             }).response.layer_id.order=Foreground;
-
-        //    println!("toplayer  id is {:?}", ui.id());//ui 的id 是不一样的，layer的id是一样的
         });
-        let central_response=CentralPanel::default().show(ctx, |ui| {
+        if !self.search.trim().is_empty() {
+            egui::SidePanel::left("search_panel").show(ctx, |ui| {
+                ui.heading("Search");
+                if let Some(id) = search_results(ui, &self.node_graph, &self.search) {
+                    self.graph_state.focus_node(&self.node_graph, id);
+                    self.pan_zoom.center_on(self.node_graph.nodes[id].node_pos);
+                }
+            });
+        }
+        //选中节点、关系连线或边界时在右侧显示样式面板和备注
+        let selected = self.graph_state.selected_nodes();
+        let selected_link = self.graph_state.selected_link;
+        let selected_boundary = self.graph_state.selected_boundary;
+        if !selected.is_empty() || selected_link.is_some() || selected_boundary.is_some() {
+            egui::SidePanel::right("style_panel").show(ctx, |ui| {
+                if !selected.is_empty() {
+                    ui.heading("Style");
+                    let defaults = self.node_graph.default_style(selected[0]);
+                    style_panel(ui, &mut self.node_graph, &selected, &defaults);
+                    egui::CollapsingHeader::new("Note").default_open(true).show(ui, |ui| {
+                        note_panel(ui, &mut self.node_graph, selected[0]);
+                    });
+                    egui::CollapsingHeader::new("Hyperlink").show(ui, |ui| {
+                        let targets = match &self.node_graph.nodes[selected[0]].hyperlink {
+                            Some(Hyperlink::Node { file: Some(file), .. }) => {
+                                let path = Hyperlink::resolve(&self.base_dir, file);
+                                self.linked_targets(path)
+                            }
+                            _ => Ok(link_targets(&self.node_graph)),
+                        };
+                        if let Err(err) = &targets {
+                            ui.colored_label(ui.visuals().error_fg_color, err);
+                        }
+                        hyperlink_panel(ui, &mut self.node_graph, selected[0], &targets.unwrap_or_default());
+                    });
+                }
+                if let Some(link_id) = selected_link {
+                    ui.heading("Link");
+                    link_panel(ui, &mut self.node_graph, link_id);
+                    if ui.button("Delete link").clicked() {
+                        self.node_graph.remove_link(link_id);
+                        self.graph_state.selected_link = None;
+                    }
+                }
+                if let Some(node_id) = selected_boundary {
+                    ui.heading("Boundary");
+                    boundary_panel(ui, &mut self.node_graph, node_id);
+                    if ui.button("Remove boundary").clicked() {
+                        self.node_graph.set_boundary(node_id, None);
+                        self.graph_state.selected_boundary = None;
+                    }
+                }
+            });
+        }
+        CentralPanel::default().show(ctx, |ui| {
             set_font(ui);
 
-         //   println!("centrallayer  id is {:?}", ui.layer_id());
             let percent=self.pan_zoom.transform.scaling;
             let movement=self.pan_zoom.transform.translation;
-            let pointer=ui.ctx().pointer_hover_pos().unwrap_or(Pos2::new(0.0,0.0));
+            let pointer=ui.ctx().pointer_hover_pos().unwrap_or_default();
 
             ui.label(format!("缩放比例 '{percent}', 平移转换 {movement},悬停坐标{pointer}"));
-            self.pan_zoom(ui);
-         //   let response=self.canvas.ui( ui,& mut self.pan_zoom);
-            match self.window_state {
-                true => {
-                    Window::new("My Window").open(&mut self.window_state).pivot(Align2::CENTER_CENTER).default_pos(ui.max_rect().center()).resize(|r| r.resizable(true)).show(ctx, |ui| {
-                        ui.label("Hello World!");
-                    });
-                }
-                false => {}
+            if self.window_state {
+                Window::new("My Window").open(&mut self.window_state).pivot(Align2::CENTER_CENTER).default_pos(ui.max_rect().center()).resize(|r| r.resizable(true)).show(ctx, |ui| {
+                    ui.label("Hello World!");
+                });
             }
 
-            let graph_response=self.node_graph.draw(ui,&mut self.pan_zoom,&mut self.graph_state);
-            handle_graph_response(&mut self.node_graph,&mut self.graph_state,graph_response);//处理response
-
+            let minimap = (!self.hide_minimap).then(Minimap::default);
+            let response = MindMap::new(&mut self.node_graph, &mut self.graph_state, &mut self.pan_zoom)
+                .minimap(minimap)
+                .settings(self.settings)
+                .base_dir(self.base_dir.clone())
+                .show(ui);
+            for event in response.events {
+                if let MindMapEvent::LinkOpened(id) = event {
+                    self.open_linked_map(id);
+                }
+            }
         });
         egui::TopBottomPanel::bottom("my_bottom_panel").show(ctx, |ui| {
-          //  println!("bottomlayer  id is {:?}", ui.id());
             ui.label("this is a bottom panel!");
         });
+        self.show_file_dialog(ctx);
+        self.confirm_linked_map(ctx);
+        if let Some(err) = &self.error {
+            let mut open = true;
+            Window::new("Error").open(&mut open).collapsible(false).pivot(Align2::CENTER_CENTER).default_pos(ctx.screen_rect().center()).show(ctx, |ui| {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            });
+            if !open {
+                self.error = None;
+            }
+        }
     }
 
 }
 
+impl MyApp {
+    //节点链接到其它导图里的节点时，读取那个导图，等用户确认后再替换当前导图
+    fn open_linked_map(&mut self, node_id: NodeId) {
+        let Some(Hyperlink::Node { file: Some(file), node }) = self.node_graph.nodes.get(node_id).and_then(|node| node.hyperlink.clone()) else {
+            return;
+        };
+        let path = Hyperlink::resolve(&self.base_dir, &file);
+        match io::load(&path, None) {
+            Ok(graph) if graph.nodes.contains_key(node) => {
+                self.pending_link = Some(PendingLink { path, graph, node, dirty: self.is_dirty() });
+            }
+            Ok(_) => self.error = Some(format!("{file} has no node {node}")),
+            Err(err) => self.error = Some(err),
+        }
+    }
+    //询问是否用链接的导图替换当前导图；当前导图有没保存的修改时可以先保存，没有文件时先另存为
+    fn confirm_linked_map(&mut self, ctx: &Context) {
+        let Some(PendingLink { path, dirty, .. }) = &self.pending_link else { return };
+        let dirty = *dirty;
+        let (mut save, mut open, mut cancel) = (false, false, false);
+        Window::new("Open linked map").collapsible(false).resizable(false).pivot(Align2::CENTER_CENTER).default_pos(ctx.screen_rect().center()).show(ctx, |ui| {
+            ui.label(format!("Open {}? It replaces the current map.", path.display()));
+            if dirty {
+                ui.label("The current map has unsaved changes.");
+            }
+            ui.horizontal(|ui| {
+                if dirty {
+                    save = ui.button("Save and open").clicked();
+                }
+                open = ui.button(if dirty { "Open without saving" } else { "Open" }).clicked();
+                cancel = ui.button("Cancel").clicked();
+            });
+        });
+        if save {
+            match self.file.clone() {
+                Some(file) => {
+                    if !self.save(file) {
+                        return; //保存失败，错误已经显示出来
+                    }
+                }
+                //没有文件时先另存为，保存好以后再打开一次链接
+                None => {
+                    self.file_dialog = Some(self.file_dialog(true));
+                    self.pending_link = None;
+                    return;
+                }
+            }
+        }
+        if cancel {
+            self.pending_link = None;
+        }
+        if !(save || open) {
+            return;
+        }
+        let Some(PendingLink { path, graph, node, .. }) = self.pending_link.take() else { return };
+        self.replace_map(path, graph);
+        self.graph_state.focus_node(&self.node_graph, node);
+        self.pan_zoom.center_on(self.node_graph.nodes[node].node_pos);
+    }
+    //用从 path 读出的导图替换当前导图
+    fn replace_map(&mut self, path: PathBuf, graph: NodeGraph) {
+        self.graph_state = GraphState::from_graph(&graph);
+        self.saved = io::to_native(&graph).ok();
+        self.node_graph = graph;
+        self.base_dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        self.file = Some(path);
+    }
+    //当前导图有没有打开或保存以后的修改；新建的空导图不算
+    fn is_dirty(&self) -> bool {
+        match &self.saved {
+            Some(saved) => io::to_native(&self.node_graph).ok().as_ref() != Some(saved),
+            None => !self.node_graph.nodes.is_empty(),
+        }
+    }
+    //按扩展名的格式保存到 path，之后“保存”都写到这个文件；svg 和 png 只是导出，不改当前文件
+    //返回是否保存成功，失败时显示错误
+    fn save(&mut self, path: PathBuf) -> bool {
+        if let Err(err) = io::save(&self.node_graph, &path, None) {
+            self.error = Some(err);
+            return false;
+        }
+        if io::Format::from_path(&path).is_some_and(|format| format.can_import()) {
+            self.saved = io::to_native(&self.node_graph).ok();
+            self.base_dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+            self.file = Some(path);
+        }
+        true
+    }
+    //新的打开或另存为对话框，预先填好当前文件的路径，没有时是当前目录下的新文件
+    fn file_dialog(&self, save: bool) -> FileDialog {
+        let path = self.file.as_ref().map_or_else(|| "untitled.mindmap".to_owned(), |file| file.display().to_string());
+        FileDialog { save, path, dirty: !save && self.is_dirty() }
+    }
+    //输入路径打开或另存为；打开时当前导图有没保存的修改要先确认
+    fn show_file_dialog(&mut self, ctx: &Context) {
+        let Some(dialog) = &mut self.file_dialog else { return };
+        let (mut confirmed, mut cancel) = (false, false);
+        let title = if dialog.save { "Save as" } else { "Open" };
+        Window::new(title).collapsible(false).resizable(false).pivot(Align2::CENTER_CENTER).default_pos(ctx.screen_rect().center()).show(ctx, |ui| {
+            ui.label("File (.mindmap, .md, .opml or .mm; saving also exports .svg and .png)");
+            let response = ui.text_edit_singleline(&mut dialog.path);
+            confirmed = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if dialog.dirty {
+                ui.label("Unsaved changes in the current map are lost.");
+            }
+            ui.horizontal(|ui| {
+                confirmed |= ui.button(title).clicked();
+                cancel = ui.button("Cancel").clicked();
+            });
+        });
+        if cancel {
+            self.file_dialog = None;
+        }
+        if !confirmed {
+            return;
+        }
+        let Some(FileDialog { save, path, .. }) = self.file_dialog.take() else { return };
+        let path = PathBuf::from(path.trim());
+        if save {
+            self.save(path);
+            return;
+        }
+        match io::load(&path, None) {
+            Ok(graph) => {
+                self.replace_map(path, graph);
+                self.pan_zoom.zoom_to_fit(&self.node_graph);
+            }
+            Err(err) => self.error = Some(err),
+        }
+    }
+    //链接导图里可以作为目标的节点，文件修改过或者还没读过时重新读取
+    fn linked_targets(&mut self, path: PathBuf) -> Result<Vec<(NodeId, String)>, String> {
+        let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok();
+        if let Some(map) = self.linked_maps.get(&path).filter(|map| map.modified == modified) {
+            return map.targets.clone();
+        }
+        let targets = io::load(&path, None).map(|graph| link_targets(&graph));
+        self.linked_maps.insert(path, LinkedMap { modified, targets: targets.clone() });
+        targets
+    }
+}
+
 fn main() {
     let options = eframe::NativeOptions::default();
-    let mut myapp=MyApp::default();
- //   handle_state(&mut myapp.graph_state,myapp.graph_response);
+    let myapp=MyApp::default();
     eframe::run_native(
         "Infinite Zoom Canvas",
         options,
@@ -230,4 +488,3 @@ fn set_font( ui:&mut Ui){
         .insert(0, "my_font".to_owned());
     ui.ctx().set_fonts(fonts);
 }
-
@@ -0,0 +1,121 @@
+//! 手动排版用的对齐和等距分布，只移动指定的节点，子节点不跟着移动
+
+use super::geometry::Vector;
+use super::node_graph::{NodeGraph, NodeId};
+
+/// 对齐方式，以所有节点的包围盒为准
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    /// 中心在同一条竖线上
+    CenterHorizontal,
+    /// 中心在同一条横线上
+    CenterVertical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+impl NodeGraph {
+    //把节点按包围盒的某条边或中线对齐
+    pub fn align_nodes(&mut self, ids: &[NodeId], alignment: Alignment) {
+        let Some(bounds) = self.bounds_of(ids.iter().copied()) else {
+            return;
+        };
+        for &id in ids {
+            let Some(rect) = self.nodes.get(id).map(|node| node.rect()) else {
+                continue;
+            };
+            let delta = match alignment {
+                Alignment::Left => Vector::new(bounds.min.x - rect.min.x, 0.0),
+                Alignment::Right => Vector::new(bounds.max.x - rect.max.x, 0.0),
+                Alignment::Top => Vector::new(0.0, bounds.min.y - rect.min.y),
+                Alignment::Bottom => Vector::new(0.0, bounds.max.y - rect.max.y),
+                Alignment::CenterHorizontal => Vector::new(bounds.center().x - rect.center().x, 0.0),
+                Alignment::CenterVertical => Vector::new(0.0, bounds.center().y - rect.center().y),
+            };
+            self.move_node(id, delta);
+        }
+    }
+
+    //首尾两个节点不动，中间的节点移动到相邻节点间距相等的位置，少于 3 个节点时不做任何事
+    pub fn distribute_nodes(&mut self, ids: &[NodeId], axis: Axis) {
+        let mut items: Vec<(NodeId, f32, f32)> = ids.iter()
+            .filter_map(|&id| self.nodes.get(id).map(|node| {
+                let rect = node.rect();
+                match axis {
+                    Axis::Horizontal => (id, rect.min.x, rect.width()),
+                    Axis::Vertical => (id, rect.min.y, rect.height()),
+                }
+            }))
+            .collect();
+        if items.len() < 3 {
+            return;
+        }
+        items.sort_by(|a, b| (a.1 + a.2 / 2.0).total_cmp(&(b.1 + b.2 / 2.0)));
+
+        let (_, first_min, _) = items[0];
+        let (_, last_min, last_size) = items[items.len() - 1];
+        let total_size: f32 = items.iter().map(|item| item.2).sum();
+        let gap = (last_min + last_size - first_min - total_size) / (items.len() - 1) as f32;
+
+        let mut next = first_min;
+        for &(id, min, size) in &items {
+            let delta = next - min;
+            match axis {
+                Axis::Horizontal => self.move_node(id, Vector::new(delta, 0.0)),
+                Axis::Vertical => self.move_node(id, Vector::new(0.0, delta)),
+            }
+            next += size + gap;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Point;
+
+    //三个大小不同、位置错开的根节点
+    fn scattered() -> (NodeGraph, Vec<NodeId>) {
+        let mut graph = NodeGraph::default();
+        let ids: Vec<NodeId> = [(0.0, 0.0), (130.0, 70.0), (500.0, -40.0)]
+            .into_iter()
+            .map(|(x, y)| graph.add_node_with_position(Point::new(x, y)))
+            .collect();
+        graph.nodes[ids[1]].node_size = Vector::new(200.0, 60.0);
+        (graph, ids)
+    }
+
+    #[test]
+    fn align_moves_nodes_to_the_shared_edge_only() {
+        let (mut graph, ids) = scattered();
+        let bounds = graph.bounds().unwrap();
+        graph.align_nodes(&ids, Alignment::Left);
+        assert!(ids.iter().all(|id| graph.nodes[*id].rect().min.x == bounds.min.x));
+        assert_eq!(graph.nodes[ids[2]].node_pos.y, -40.0);
+        graph.align_nodes(&ids, Alignment::CenterVertical);
+        let center = graph.nodes[ids[0]].node_pos.y;
+        assert!(ids.iter().all(|id| graph.nodes[*id].node_pos.y == center));
+    }
+
+    #[test]
+    fn distribute_leaves_equal_gaps_between_neighbours() {
+        let (mut graph, ids) = scattered();
+        let (first, last) = (graph.nodes[ids[0]].node_pos, graph.nodes[ids[2]].node_pos);
+        graph.distribute_nodes(&ids, Axis::Horizontal);
+        let rects: Vec<_> = ids.iter().map(|id| graph.nodes[*id].rect()).collect();
+        let gaps = [rects[1].min.x - rects[0].max.x, rects[2].min.x - rects[1].max.x];
+        assert!((gaps[0] - gaps[1]).abs() < 1e-3);
+        assert_eq!((graph.nodes[ids[0]].node_pos, graph.nodes[ids[2]].node_pos), (first, last));
+        //少于 3 个节点时不动
+        graph.distribute_nodes(&ids[..2], Axis::Vertical);
+        assert_eq!(graph.nodes[ids[1]].rect(), rects[1]);
+    }
+}
@@ -0,0 +1,197 @@
+//! 边界：把一个节点和它的子孙节点框在一起的外框，可以带标题和填充色
+//!
+//! 外框不保存位置，每次从子树里节点的位置算出来，所以子树移动或折叠后会自动更新。
+//! 子树里还有边界时，外层边界会留出更大的边距把内层边界也包进去。
+
+use serde::{Deserialize, Serialize};
+use super::geometry::{Color, Point, Rect, Vector};
+use super::node_graph::{NodeGraph, NodeId};
+
+/// 节点和外框之间的边距，每多一层内部边界再加一份
+pub const BOUNDARY_PADDING: f32 = 12.0;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BoundaryShape {
+    /// 包住所有节点的凸包，拐角是圆的
+    #[default]
+    RoundedHull,
+    Rectangle,
+}
+
+impl BoundaryShape {
+    pub const ALL: [BoundaryShape; 2] = [Self::RoundedHull, Self::Rectangle];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::RoundedHull => "rounded hull",
+            Self::Rectangle => "rectangle",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Boundary {
+    pub shape: BoundaryShape,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub title: String,
+    /// 外框的颜色，标题也用这个颜色
+    pub color: Color,
+    /// 填充色，None 表示不填充
+    pub fill: Option<Color>,
+}
+
+impl Default for Boundary {
+    fn default() -> Self {
+        let color = Color::from_rgb(90, 120, 200);
+        Self { shape: BoundaryShape::default(), title: String::new(), color, fill: Some(Color::from_rgba(color.r, color.g, color.b, 30)) }
+    }
+}
+
+impl NodeGraph {
+    //给节点加上或去掉边界
+    pub fn set_boundary(&mut self, node_id: NodeId, boundary: Option<Boundary>) {
+        if let Some(node) = self.nodes.get_mut(node_id) {
+            node.boundary = boundary;
+            self.touch();
+        }
+    }
+    //node_id 的边界外框，是一个按顺时针排列的凸多边形；visible 过滤掉折叠起来的节点，节点本身不可见或没有边界时返回 None
+    pub fn boundary_outline(&self, node_id: NodeId, visible: &impl Fn(NodeId) -> bool) -> Option<Vec<Point>> {
+        let boundary = self.nodes.get(node_id)?.boundary.as_ref()?;
+        if !visible(node_id) {
+            return None;
+        }
+        let mut subtree = self.query_all_children_nodes(node_id);
+        subtree.push(node_id);
+        subtree.retain(|id| visible(*id));
+        let padding = BOUNDARY_PADDING * (1 + self.inner_boundary_levels(node_id)) as f32;
+        let bounds = self.bounds_of(subtree.iter().copied())?;
+        match boundary.shape {
+            BoundaryShape::Rectangle => {
+                let rect = bounds.expand(padding);
+                Some(vec![rect.min, Point::new(rect.max.x, rect.min.y), rect.max, Point::new(rect.min.x, rect.max.y)])
+            }
+            BoundaryShape::RoundedHull => {
+                //每个节点的四个角换成一圈半径为 padding 的点，这些点的凸包就是圆角的外框
+                let mut points = Vec::with_capacity(subtree.len() * 4 * CORNER_SEGMENTS);
+                for rect in subtree.iter().filter_map(|id| self.nodes.get(*id)).map(|node| node.rect()) {
+                    for corner in [rect.min, Point::new(rect.max.x, rect.min.y), rect.max, Point::new(rect.min.x, rect.max.y)] {
+                        points.extend((0..CORNER_SEGMENTS).map(|i| {
+                            let angle = i as f32 / CORNER_SEGMENTS as f32 * std::f32::consts::TAU;
+                            corner + Vector::new(angle.cos(), angle.sin()) * padding
+                        }));
+                    }
+                }
+                Some(convex_hull(points))
+            }
+        }
+    }
+    //所有边界和它们的外框，外层的边界在前面，按顺序画时内层盖住外层
+    pub fn boundary_outlines(&self, visible: &impl Fn(NodeId) -> bool) -> Vec<(NodeId, Vec<Point>)> {
+        let mut boundaries: Vec<(NodeId, Vec<Point>)> = self.nodes.iter()
+            .filter(|(_, node)| node.boundary.is_some())
+            .filter_map(|(id, _)| self.boundary_outline(id, visible).map(|outline| (id, outline)))
+            .collect();
+        boundaries.sort_by_key(|(id, _)| self.node_depth(*id));
+        boundaries
+    }
+    //子树里（不含自己）最多嵌套了几层边界
+    fn inner_boundary_levels(&self, node_id: NodeId) -> usize {
+        //栈里是节点和从 node_id 往下到它（含它）经过的边界数
+        let mut stack: Vec<(NodeId, usize)> = self.children(node_id).iter().map(|&child| (child, 0)).collect();
+        let mut levels = 0;
+        let mut visited = 0;
+        while let Some((id, above)) = stack.pop() {
+            let level = above + self.nodes.get(id).is_some_and(|node| node.boundary.is_some()) as usize;
+            levels = levels.max(level);
+            stack.extend(self.children(id).iter().map(|&child| (child, level)));
+            visited += 1;
+            if visited > self.nodes.len() {
+                break; //有环，validate 会报告
+            }
+        }
+        levels
+    }
+}
+
+//圆角用多少个点近似
+const CORNER_SEGMENTS: usize = 12;
+
+//凸包（单调链算法），按顺时针（屏幕坐标下 y 向下）返回
+fn convex_hull(mut points: Vec<Point>) -> Vec<Point> {
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    let cross = |o: Point, a: Point, b: Point| (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x);
+    let mut hull: Vec<Point> = Vec::with_capacity(points.len() * 2);
+    for pass in [points.clone(), points.into_iter().rev().collect()] {
+        let start = hull.len();
+        for p in pass {
+            while hull.len() >= start + 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0 {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        hull.pop();
+    }
+    hull
+}
+
+//点是否在凸多边形里面，多边形的顶点顺序不限
+pub fn polygon_contains(polygon: &[Point], p: Point) -> bool {
+    let n = polygon.len();
+    if n < 3 {
+        return false;
+    }
+    let sign = |a: Point, b: Point| (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x);
+    let signs: Vec<f32> = (0..n).map(|i| sign(polygon[i], polygon[(i + 1) % n])).collect();
+    signs.iter().all(|s| *s >= 0.0) || signs.iter().all(|s| *s <= 0.0)
+}
+
+//多边形的包围盒，用来放标题和做剔除
+pub fn polygon_bounds(polygon: &[Point]) -> Rect {
+    polygon.iter().fold(Rect::NOTHING, |bounds, &p| bounds.union(Rect::from_min_max(p, p)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //root 下面两个分支：a -> c -> d 和 b，边界加在 root、a、c 上
+    fn nested_boundaries() -> (NodeGraph, [NodeId; 5]) {
+        let mut graph = NodeGraph::default();
+        let root = graph.add_node_with_position(Point::ZERO);
+        let a = graph.add_node_with_father_node(root);
+        let b = graph.add_node_with_father_node(root);
+        let c = graph.add_node_with_father_node(a);
+        let d = graph.add_node_with_father_node(c);
+        let rectangle = Boundary { shape: BoundaryShape::Rectangle, ..Boundary::default() };
+        for id in [root, a, c] {
+            graph.set_boundary(id, Some(rectangle.clone()));
+        }
+        (graph, [root, a, b, c, d])
+    }
+
+    #[test]
+    fn inner_levels_count_the_deepest_nested_branch() {
+        let (mut graph, [root, a, b, c, d]) = nested_boundaries();
+        assert_eq!([root, a, b, c, d].map(|id| graph.inner_boundary_levels(id)), [2, 1, 0, 0, 0]);
+        //另一个分支上只有一层边界，不影响最深的那层
+        graph.set_boundary(b, Some(Boundary::default()));
+        assert_eq!(graph.inner_boundary_levels(root), 2);
+        graph.set_boundary(a, None);
+        assert_eq!(graph.inner_boundary_levels(root), 1);
+    }
+
+    #[test]
+    fn outer_boundaries_pad_around_inner_ones() {
+        let (graph, [root, a, _, c, d]) = nested_boundaries();
+        let bounds = graph.bounds().unwrap();
+        assert_eq!(graph.boundary_outline(root, &|_| true).unwrap()[0], bounds.expand(BOUNDARY_PADDING * 3.0).min);
+        let subtree = graph.bounds_of([a, c, d]).unwrap();
+        assert_eq!(graph.boundary_outline(a, &|_| true).unwrap()[0], subtree.expand(BOUNDARY_PADDING * 2.0).min);
+    }
+}
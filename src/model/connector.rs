@@ -0,0 +1,181 @@
+//! 父子节点之间的连线：走线方式、箭头、虚线和粗细，画布和导出共用这里的几何计算
+//!
+//! 连线的点都是从子节点的连接点开始，到父节点的展开按钮结束。
+
+use serde::{Deserialize, Serialize};
+use super::geometry::{Color, Point, Vector};
+
+/// 连线的走线方式
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConnectorKind {
+    /// 三次贝塞尔曲线
+    #[default]
+    Bezier,
+    Straight,
+    /// 水平和竖直线段组成的折线
+    Elbow,
+    /// 贝塞尔曲线，靠近父节点的一端粗，层级越深越细
+    Tapered,
+}
+
+impl ConnectorKind {
+    pub const ALL: [ConnectorKind; 4] = [Self::Bezier, Self::Straight, Self::Elbow, Self::Tapered];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Bezier => "curved",
+            Self::Straight => "straight",
+            Self::Elbow => "elbow",
+            Self::Tapered => "tapered",
+        }
+    }
+}
+
+/// 父子节点之间连线的样式
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConnectorStyle {
+    pub color: Color,
+    pub width: f32,
+    pub kind: ConnectorKind,
+    /// 在子节点一端画箭头
+    pub arrow: bool,
+    pub dashed: bool,
+}
+
+impl Default for ConnectorStyle {
+    fn default() -> Self {
+        Self { color: Color::from_rgb(255, 0, 0), width: 2.0, kind: ConnectorKind::Bezier, arrow: false, dashed: false }
+    }
+}
+
+impl ConnectorStyle {
+    //depth 层（根节点为 1）节点处的线宽，只有 Tapered 随层级变细
+    pub fn width_at(&self, depth: usize) -> f32 {
+        match self.kind {
+            ConnectorKind::Tapered => {
+                let width = self.width * 4.0 * 0.6_f32.powi(depth.saturating_sub(1) as i32);
+                width.max(self.width * 0.5)
+            }
+            _ => self.width,
+        }
+    }
+    //箭头的长度，跟着线宽变大
+    pub fn arrow_size(&self) -> f32 {
+        4.0 + self.width * 3.0
+    }
+    //虚线的线段和间隔长度
+    pub fn dash_pattern(&self) -> (f32, f32) {
+        (self.width * 4.0, self.width * 3.0)
+    }
+}
+
+//按走线方式把 `curve_control_points` 给出的控制点变成折线，曲线用 segments 段近似
+pub fn connector_points(kind: ConnectorKind, [p0, p1, p2, p3]: [Point; 4], segments: usize) -> Vec<Point> {
+    match kind {
+        ConnectorKind::Straight => vec![p0, p3],
+        //控制点本身就是先水平、再竖直、再水平的折线
+        ConnectorKind::Elbow => vec![p0, p1, p2, p3],
+        ConnectorKind::Bezier | ConnectorKind::Tapered => {
+            let segments = segments.max(1);
+            (0..=segments).map(|i| cubic_point([p0, p1, p2, p3], i as f32 / segments as f32)).collect()
+        }
+    }
+}
+
+//连线的中点，用来放标签
+pub fn connector_midpoint(kind: ConnectorKind, [p0, p1, p2, p3]: [Point; 4]) -> Point {
+    let middle = |a: Point, b: Point| a + (b - a) * 0.5;
+    match kind {
+        ConnectorKind::Straight => middle(p0, p3),
+        ConnectorKind::Elbow => middle(p1, p2),
+        ConnectorKind::Bezier | ConnectorKind::Tapered => cubic_point([p0, p1, p2, p3], 0.5),
+    }
+}
+
+//三次贝塞尔曲线上参数为 t 的点
+pub(crate) fn cubic_point([p0, p1, p2, p3]: [Point; 4], t: f32) -> Point {
+    let s = 1.0 - t;
+    let v = p0.to_vector() * (s * s * s)
+        + p1.to_vector() * (3.0 * s * s * t)
+        + p2.to_vector() * (3.0 * s * t * t)
+        + p3.to_vector() * (t * t * t);
+    Point::new(v.x, v.y)
+}
+
+//粗细渐变的折线外轮廓，从 start_width 线性变到 end_width，返回可以直接填充的多边形
+pub fn tapered_outline(points: &[Point], start_width: f32, end_width: f32) -> Vec<Point> {
+    let n = points.len();
+    if n < 2 {
+        return Vec::new();
+    }
+    let mut left = Vec::with_capacity(n);
+    let mut right = Vec::with_capacity(n);
+    for (i, &p) in points.iter().enumerate() {
+        let before = points[i.saturating_sub(1)];
+        let after = points[(i + 1).min(n - 1)];
+        let normal = unit(after - before).map(|d| Vector::new(-d.y, d.x)).unwrap_or(Vector::ZERO);
+        let t = i as f32 / (n - 1) as f32;
+        let half = (start_width + (end_width - start_width) * t) / 2.0;
+        left.push(p + normal * half);
+        right.push(p - normal * half);
+    }
+    left.extend(right.into_iter().rev());
+    left
+}
+
+//箭头三角形，尖端在 tip，朝着从 from 到 tip 的方向
+pub fn arrow_head(tip: Point, from: Point, size: f32) -> Option<[Point; 3]> {
+    let direction = unit(tip - from)?;
+    let normal = Vector::new(-direction.y, direction.x);
+    let base = tip - direction * size;
+    Some([tip, base + normal * (size * 0.5), base - normal * (size * 0.5)])
+}
+
+fn unit(v: Vector) -> Option<Vector> {
+    let length = v.length();
+    (length > f32::EPSILON).then(|| v / length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTROL: [Point; 4] = [Point::new(0.0, 0.0), Point::new(50.0, 0.0), Point::new(50.0, 100.0), Point::new(100.0, 100.0)];
+
+    #[test]
+    fn only_tapered_connectors_thin_out_with_depth() {
+        let tapered = ConnectorStyle { kind: ConnectorKind::Tapered, width: 2.0, ..ConnectorStyle::default() };
+        assert_eq!(tapered.width_at(1), 8.0);
+        assert!((tapered.width_at(2) - 4.8).abs() < 1e-4);
+        let widths: Vec<f32> = (1..10).map(|depth| tapered.width_at(depth)).collect();
+        assert!(widths.windows(2).all(|w| w[1] <= w[0]));
+        //再深也不细于一半线宽
+        assert_eq!(tapered.width_at(50), 1.0);
+        let straight = ConnectorStyle { kind: ConnectorKind::Straight, ..tapered };
+        assert_eq!(straight.width_at(5), 2.0);
+    }
+
+    #[test]
+    fn connector_points_start_and_end_at_the_anchors() {
+        for kind in ConnectorKind::ALL {
+            let points = connector_points(kind, CONTROL, 16);
+            assert_eq!((points[0], *points.last().unwrap()), (CONTROL[0], CONTROL[3]), "{}", kind.name());
+        }
+        assert_eq!(connector_points(ConnectorKind::Bezier, CONTROL, 0).len(), 2);
+        assert_eq!(connector_points(ConnectorKind::Elbow, CONTROL, 16), CONTROL.to_vec());
+        assert_eq!(connector_midpoint(ConnectorKind::Elbow, CONTROL), Point::new(50.0, 50.0));
+        assert_eq!(connector_midpoint(ConnectorKind::Bezier, CONTROL), Point::new(50.0, 50.0));
+    }
+
+    #[test]
+    fn tapered_outlines_and_arrows_follow_the_line() {
+        let outline = tapered_outline(&[Point::new(0.0, 0.0), Point::new(100.0, 0.0)], 8.0, 2.0);
+        assert_eq!(outline, vec![Point::new(0.0, 4.0), Point::new(100.0, 1.0), Point::new(100.0, -1.0), Point::new(0.0, -4.0)]);
+        assert!(tapered_outline(&[Point::ZERO], 8.0, 2.0).is_empty());
+        let arrow = arrow_head(Point::new(10.0, 0.0), Point::ZERO, 4.0).unwrap();
+        assert_eq!(arrow, [Point::new(10.0, 0.0), Point::new(6.0, 2.0), Point::new(6.0, -2.0)]);
+        //两点重合时没有方向
+        assert_eq!(arrow_head(Point::ZERO, Point::ZERO, 4.0), None);
+    }
+}
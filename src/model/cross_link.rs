@@ -0,0 +1,149 @@
+//! 关系连线：树结构以外的"参见"关系，可以连接任意两个节点，包括不同分支甚至不同根节点下的节点
+//!
+//! 两个控制点保存为相对于两端节点中心的偏移，节点移动时曲线跟着移动。
+
+use serde::{Deserialize, Serialize};
+use super::connector::{ConnectorKind, ConnectorStyle};
+use super::geometry::{Color, Point, Rect, Vector};
+use super::node_graph::{NodeGraph, NodeId};
+
+slotmap::new_key_type! {
+    pub struct LinkId;
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrossLink {
+    pub source: NodeId,
+    pub target: NodeId,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub label: String,
+    /// 靠近 source 的控制点，相对于 source 中心的偏移
+    pub source_control: Vector,
+    /// 靠近 target 的控制点，相对于 target 中心的偏移
+    pub target_control: Vector,
+    #[serde(default = "CrossLink::default_style")]
+    pub style: ConnectorStyle,
+}
+
+impl CrossLink {
+    //默认是带箭头的紫色虚线，和父子连线区分开
+    pub fn default_style() -> ConnectorStyle {
+        ConnectorStyle {
+            color: Color::from_rgb(130, 80, 200),
+            width: 1.5,
+            kind: ConnectorKind::Bezier,
+            arrow: true,
+            dashed: true,
+        }
+    }
+}
+
+impl NodeGraph {
+    //从 source 连到 target，两个节点必须存在且不相同；控制点默认让曲线向一侧拱起
+    pub fn add_link(&mut self, source: NodeId, target: NodeId) -> Option<LinkId> {
+        let from = self.nodes.get(source)?.node_pos;
+        let to = self.nodes.get(target)?.node_pos;
+        if source == target {
+            return None;
+        }
+        let d = to - from;
+        let bulge = Vector::new(d.y, -d.x) * 0.25;
+        Some(self.links.insert(CrossLink {
+            source,
+            target,
+            label: String::new(),
+            source_control: d * 0.3 + bulge,
+            target_control: -d * 0.3 + bulge,
+            style: CrossLink::default_style(),
+        }))
+    }
+    pub fn remove_link(&mut self, link_id: LinkId) -> Option<CrossLink> {
+        self.links.remove(link_id)
+    }
+    //连到 node_id 或从 node_id 出发的连线
+    pub fn links_of(&self, node_id: NodeId) -> impl Iterator<Item = LinkId> + '_ {
+        self.links.iter()
+            .filter(move |(_, link)| link.source == node_id || link.target == node_id)
+            .map(|(id, _)| id)
+    }
+    //去掉端点已经不存在的连线，删除节点后调用
+    pub(crate) fn remove_dangling_links(&mut self) {
+        let nodes = &self.nodes;
+        self.links.retain(|_, link| nodes.contains_key(link.source) && nodes.contains_key(link.target));
+    }
+    //连线的四个控制点，和父子连线一样从箭头所在的一端（target）开始，端点在节点的边框上
+    pub fn link_control_points(&self, link_id: LinkId) -> Option<[Point; 4]> {
+        let link = self.links.get(link_id)?;
+        let source = self.nodes.get(link.source)?;
+        let target = self.nodes.get(link.target)?;
+        let source_control = source.node_pos + link.source_control;
+        let target_control = target.node_pos + link.target_control;
+        Some([
+            border_point(target.rect(), target_control),
+            target_control,
+            source_control,
+            border_point(source.rect(), source_control),
+        ])
+    }
+    //把第 index 个控制点（0 靠近 source，1 靠近 target）移动到世界坐标 pos
+    pub fn set_link_control(&mut self, link_id: LinkId, index: usize, pos: Point) {
+        let Some(link) = self.links.get(link_id) else { return };
+        let end = if index == 0 { link.source } else { link.target };
+        let Some(center) = self.nodes.get(end).map(|node| node.node_pos) else { return };
+        let link = &mut self.links[link_id];
+        if index == 0 {
+            link.source_control = pos - center;
+        } else {
+            link.target_control = pos - center;
+        }
+    }
+}
+
+//从矩形中心朝 toward 方向出发和边框的交点，toward 在矩形内部时直接返回 toward
+fn border_point(rect: Rect, toward: Point) -> Point {
+    let center = rect.center();
+    let d = toward - center;
+    let half = rect.size() * 0.5;
+    let tx = if d.x.abs() > f32::EPSILON { half.x / d.x.abs() } else { f32::INFINITY };
+    let ty = if d.y.abs() > f32::EPSILON { half.y / d.y.abs() } else { f32::INFINITY };
+    center + d * tx.min(ty).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removing_a_branch_removes_its_links() {
+        let mut graph = NodeGraph::default();
+        let root = graph.add_node_with_position(Point::ZERO);
+        let a = graph.add_node_with_father_node(root);
+        let b = graph.add_node_with_father_node(a);
+        let other = graph.add_node_with_position(Point::new(300.0, 0.0));
+        assert_eq!(graph.add_link(other, other), None);
+        let kept = graph.add_link(root, other).unwrap();
+        let dropped = graph.add_link(other, b).unwrap();
+        assert_eq!(graph.links_of(other).count(), 2);
+        //删除 a 时连同子节点 b 的连线一起删掉
+        graph.remove_node(a);
+        assert!(graph.links.contains_key(kept));
+        assert!(!graph.links.contains_key(dropped));
+        assert_eq!(graph.links_of(other).collect::<Vec<_>>(), vec![kept]);
+    }
+
+    #[test]
+    fn control_points_move_with_their_nodes() {
+        let mut graph = NodeGraph::default();
+        let source = graph.add_node_with_position(Point::ZERO);
+        let target = graph.add_node_with_position(Point::new(300.0, 0.0));
+        let link = graph.add_link(source, target).unwrap();
+        graph.set_link_control(link, 1, Point::new(300.0, 200.0));
+        let [start, target_control, _, end] = graph.link_control_points(link).unwrap();
+        assert_eq!(target_control, Point::new(300.0, 200.0));
+        //端点落在节点边框上，朝着控制点的方向
+        assert_eq!(start, Point::new(300.0, 25.0));
+        assert!(graph.nodes[source].rect().expand(0.01).contains(end) && !graph.nodes[source].rect().expand(-0.01).contains(end));
+        graph.move_node(target, Vector::new(0.0, 50.0));
+        assert_eq!(graph.link_control_points(link).unwrap()[1], Point::new(300.0, 250.0));
+    }
+}
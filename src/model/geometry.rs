@@ -0,0 +1,240 @@
+//! 模型层使用的几何和颜色类型，不依赖 egui，界面层再转换成 egui 的类型
+
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+use serde::{Deserialize, Serialize};
+
+/// 世界坐标下的一个点
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// 位移或尺寸
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Vector {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// 轴对齐的矩形
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Rect {
+    pub min: Point,
+    pub max: Point,
+}
+
+/// 非预乘的 RGBA 颜色，序列化成 `[r, g, b, a]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "[u8; 4]", into = "[u8; 4]")]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+pub const fn point(x: f32, y: f32) -> Point {
+    Point { x, y }
+}
+
+pub const fn vector(x: f32, y: f32) -> Vector {
+    Vector { x, y }
+}
+
+impl Point {
+    pub const ZERO: Point = point(0.0, 0.0);
+
+    pub const fn new(x: f32, y: f32) -> Self {
+        point(x, y)
+    }
+    pub fn to_vector(self) -> Vector {
+        vector(self.x, self.y)
+    }
+    pub fn distance(self, other: Point) -> f32 {
+        (self - other).length()
+    }
+}
+
+impl Vector {
+    pub const ZERO: Vector = vector(0.0, 0.0);
+
+    pub const fn new(x: f32, y: f32) -> Self {
+        vector(x, y)
+    }
+    pub fn length(self) -> f32 {
+        self.x.hypot(self.y)
+    }
+}
+
+impl Add<Vector> for Point {
+    type Output = Point;
+    fn add(self, rhs: Vector) -> Point {
+        point(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl AddAssign<Vector> for Point {
+    fn add_assign(&mut self, rhs: Vector) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub<Vector> for Point {
+    type Output = Point;
+    fn sub(self, rhs: Vector) -> Point {
+        point(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl SubAssign<Vector> for Point {
+    fn sub_assign(&mut self, rhs: Vector) {
+        *self = *self - rhs;
+    }
+}
+
+impl Sub for Point {
+    type Output = Vector;
+    fn sub(self, rhs: Point) -> Vector {
+        vector(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+    fn add(self, rhs: Vector) -> Vector {
+        vector(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl AddAssign for Vector {
+    fn add_assign(&mut self, rhs: Vector) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Vector {
+    type Output = Vector;
+    fn sub(self, rhs: Vector) -> Vector {
+        vector(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+    fn neg(self) -> Vector {
+        vector(-self.x, -self.y)
+    }
+}
+
+impl Mul<f32> for Vector {
+    type Output = Vector;
+    fn mul(self, rhs: f32) -> Vector {
+        vector(self.x * rhs, self.y * rhs)
+    }
+}
+
+//逐分量相乘，用来取尺寸的一部分，比如 size * vector(0.5, 0.0)
+impl Mul for Vector {
+    type Output = Vector;
+    fn mul(self, rhs: Vector) -> Vector {
+        vector(self.x * rhs.x, self.y * rhs.y)
+    }
+}
+
+impl Div<f32> for Vector {
+    type Output = Vector;
+    fn div(self, rhs: f32) -> Vector {
+        vector(self.x / rhs, self.y / rhs)
+    }
+}
+
+impl Rect {
+    /// 不包含任何点的矩形，和其它矩形 union 后得到那个矩形
+    pub const NOTHING: Rect = Rect {
+        min: point(f32::INFINITY, f32::INFINITY),
+        max: point(f32::NEG_INFINITY, f32::NEG_INFINITY),
+    };
+
+    pub fn from_min_max(min: Point, max: Point) -> Self {
+        Rect { min, max }
+    }
+    pub fn from_min_size(min: Point, size: Vector) -> Self {
+        Rect { min, max: min + size }
+    }
+    pub fn from_center_size(center: Point, size: Vector) -> Self {
+        Rect { min: center - size / 2.0, max: center + size / 2.0 }
+    }
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+    pub fn size(&self) -> Vector {
+        self.max - self.min
+    }
+    pub fn center(&self) -> Point {
+        point((self.min.x + self.max.x) / 2.0, (self.min.y + self.max.y) / 2.0)
+    }
+    //宽或高小于 0，比如 NOTHING
+    pub fn is_negative(&self) -> bool {
+        self.max.x < self.min.x || self.max.y < self.min.y
+    }
+    pub fn union(self, other: Rect) -> Rect {
+        Rect {
+            min: point(self.min.x.min(other.min.x), self.min.y.min(other.min.y)),
+            max: point(self.max.x.max(other.max.x), self.max.y.max(other.max.y)),
+        }
+    }
+    pub fn expand(self, amount: f32) -> Rect {
+        Rect { min: self.min - vector(amount, amount), max: self.max + vector(amount, amount) }
+    }
+    pub fn contains(&self, p: Point) -> bool {
+        self.min.x <= p.x && p.x <= self.max.x && self.min.y <= p.y && p.y <= self.max.y
+    }
+    pub fn intersects(&self, other: Rect) -> bool {
+        self.min.x <= other.max.x && other.min.x <= self.max.x && self.min.y <= other.max.y && other.min.y <= self.max.y
+    }
+}
+
+impl Color {
+    pub const BLACK: Color = Color::from_rgb(0, 0, 0);
+    pub const WHITE: Color = Color::from_rgb(255, 255, 255);
+    pub const TRANSPARENT: Color = Color::from_rgba(0, 0, 0, 0);
+
+    pub const fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b, a: 255 }
+    }
+    pub const fn from_rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Color { r, g, b, a }
+    }
+    pub const fn from_gray(l: u8) -> Self {
+        Color::from_rgb(l, l, l)
+    }
+    //按 t 在两个颜色之间线性插值，t 为 0 时是自己，1 时是 other
+    pub fn lerp(self, other: Color, t: f32) -> Color {
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Color::from_rgba(mix(self.r, other.r), mix(self.g, other.g), mix(self.b, other.b), mix(self.a, other.a))
+    }
+    //感知亮度，0 到 1
+    pub fn luminance(self) -> f32 {
+        (0.299 * self.r as f32 + 0.587 * self.g as f32 + 0.114 * self.b as f32) / 255.0
+    }
+    //"#rrggbb" 形式，导出 svg 时用
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl From<[u8; 4]> for Color {
+    fn from([r, g, b, a]: [u8; 4]) -> Self {
+        Color { r, g, b, a }
+    }
+}
+
+impl From<Color> for [u8; 4] {
+    fn from(c: Color) -> Self {
+        [c.r, c.g, c.b, c.a]
+    }
+}
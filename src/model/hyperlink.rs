@@ -0,0 +1,94 @@
+//! 节点上的超链接：网址、本地文件，或者同一个导图、另一个导图文件里的节点
+//!
+//! 目标节点被删除后链接保留下来，可以用 `NodeGraph::broken_links` 找出来。
+
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use super::node_graph::{NodeGraph, NodeId};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Hyperlink {
+    /// 网址，比如 https://... 或 mailto:...
+    Url(String),
+    /// 本地文件，相对路径相对于导图文件所在的目录
+    File(String),
+    /// 导图里的节点，file 为 None 时是同一个导图
+    Node {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        file: Option<String>,
+        node: NodeId,
+    },
+}
+
+impl Hyperlink {
+    pub const KINDS: [&'static str; 3] = ["url", "file", "node"];
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Url(_) => Self::KINDS[0],
+            Self::File(_) => Self::KINDS[1],
+            Self::Node { .. } => Self::KINDS[2],
+        }
+    }
+
+    //链接里的本地文件或导图文件的路径，相对路径从 base_dir（导图文件所在的目录）开始
+    pub fn resolve(base_dir: &Path, path: &str) -> PathBuf {
+        base_dir.join(path.trim())
+    }
+
+    //本地文件的 file:// 地址，交给系统打开用；路径里的空格、# 等字符按 URL 规则转义，Windows 的盘符和 \ 也能处理
+    pub fn file_url(path: &Path) -> Option<String> {
+        let path = std::path::absolute(path).ok()?;
+        url::Url::from_file_path(path).ok().map(String::from)
+    }
+
+    //显示在提示里的链接目标，本导图里的节点显示节点文字
+    pub fn describe(&self, graph: &NodeGraph) -> String {
+        match self {
+            Self::Url(url) => url.clone(),
+            Self::File(path) => path.clone(),
+            Self::Node { file: Some(file), node } => format!("{file} → {node}"),
+            Self::Node { file: None, node } => match graph.nodes.get(*node) {
+                Some(target) => format!("→ {}", target.node_text.lines().next().unwrap_or_default()),
+                None => format!("→ missing {node}"),
+            },
+        }
+    }
+}
+
+impl NodeGraph {
+    pub fn set_hyperlink(&mut self, node_id: NodeId, hyperlink: Option<Hyperlink>) {
+        if let Some(node) = self.nodes.get_mut(node_id) {
+            node.hyperlink = hyperlink;
+        }
+    }
+    //链接指向本导图里已经不存在的节点，或者网址、路径是空的
+    pub fn is_link_broken(&self, node_id: NodeId) -> bool {
+        match self.nodes.get(node_id).and_then(|node| node.hyperlink.as_ref()) {
+            Some(Hyperlink::Url(target) | Hyperlink::File(target)) => target.trim().is_empty(),
+            Some(Hyperlink::Node { file: None, node }) => !self.nodes.contains_key(*node),
+            _ => false,
+        }
+    }
+    //有失效链接的节点，按 id 排序；其它文件里的目标要读取文件才知道，见 `io::check_links`
+    pub fn broken_links(&self) -> Vec<NodeId> {
+        let mut broken: Vec<NodeId> = self.nodes.keys().filter(|id| self.is_link_broken(*id)).collect();
+        broken.sort_unstable();
+        broken
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_urls_escape_special_characters() {
+        let base = std::env::current_dir().unwrap();
+        let url = Hyperlink::file_url(&Hyperlink::resolve(&base, " notes/a b#1%.md ")).unwrap();
+        assert!(url.starts_with("file:///"));
+        assert!(url.ends_with("/notes/a%20b%231%25.md"));
+        assert_eq!(url::Url::parse(&url).unwrap().to_file_path().unwrap(), base.join("notes/a b#1%.md"));
+    }
+}
@@ -0,0 +1,31 @@
+//! 与界面无关的文档模型：节点树、文本、颜色和几何信息
+//!
+//! 这里只用普通的数据类型，不依赖 egui/eframe，命令行工具和格式转换只需要这一层。
+
+pub mod arrange;
+pub mod boundary;
+pub mod connector;
+pub mod cross_link;
+pub mod geometry;
+pub mod hyperlink;
+pub mod node;
+pub mod node_graph;
+pub mod rich_text;
+pub mod spatial_index;
+pub mod summary;
+pub mod theme;
+pub mod tree;
+
+pub use arrange::{Alignment, Axis};
+pub use boundary::{Boundary, BoundaryShape};
+pub use connector::{ConnectorKind, ConnectorStyle};
+pub use cross_link::{CrossLink, LinkId};
+pub use geometry::{Color, Point, Rect, Vector};
+pub use hyperlink::Hyperlink;
+pub use node::{Node, NodeShape, NodeStyle, ResolvedStyle, TextLayout};
+pub use node_graph::{GraphStats, NodeGraph, NodeId};
+pub use rich_text::TextSpan;
+pub use spatial_index::{SpatialIndex, SpatialItem};
+pub use summary::{Summary, SummaryId};
+pub use theme::Theme;
+pub use tree::TreeInfo;
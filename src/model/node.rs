@@ -0,0 +1,393 @@
+use serde::{Deserialize, Serialize};
+use super::boundary::Boundary;
+use super::connector::ConnectorKind;
+use super::hyperlink::Hyperlink;
+use super::geometry::{Color, Point, Rect, Vector, vector};
+use super::node_graph::NodeId;
+
+/// 节点的默认大小
+pub const DEFAULT_NODE_SIZE: Vector = vector(100.0, 50.0);
+/// 节点的默认颜色
+pub const DEFAULT_NODE_COLOR: Color = Color::from_rgb(150, 150, 250);
+/// 节点文字的默认字号
+pub const DEFAULT_FONT_SIZE: f32 = 14.0;
+/// 展开按钮的直径
+pub const BUTTON_SIZE: f32 = 10.0;
+
+/// 节点的外形，决定绘制、命中检测和连线的连接点
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NodeShape {
+    #[default]
+    RoundedRect,
+    Ellipse,
+    /// 两端是半圆的圆角矩形
+    Capsule,
+    Diamond,
+    /// 只在文字下面画一条线，常用于叶子节点
+    Underline,
+}
+
+impl NodeShape {
+    pub const ALL: [NodeShape; 5] = [Self::RoundedRect, Self::Ellipse, Self::Capsule, Self::Diamond, Self::Underline];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::RoundedRect => "rounded rect",
+            Self::Ellipse => "ellipse",
+            Self::Capsule => "capsule",
+            Self::Diamond => "diamond",
+            Self::Underline => "underline",
+        }
+    }
+
+    //能放下 content 大小的矩形内容时外形需要的大小
+    pub fn size_for_content(self, content: Vector) -> Vector {
+        match self {
+            Self::RoundedRect | Self::Underline => content,
+            //内接矩形和椭圆同宽高比时，椭圆的轴是矩形边长的 √2 倍
+            Self::Ellipse => content * std::f32::consts::SQRT_2,
+            //两端各加半个高度的半圆
+            Self::Capsule => vector(content.x + content.y, content.y),
+            Self::Diamond => content * 2.0,
+        }
+    }
+
+    //点是否落在 rect 里画出的这个外形上
+    pub fn contains(self, rect: Rect, p: Point) -> bool {
+        if !rect.contains(p) {
+            return false;
+        }
+        let half = rect.size() / 2.0;
+        if half.x <= 0.0 || half.y <= 0.0 {
+            return false;
+        }
+        let d = p - rect.center();
+        match self {
+            Self::RoundedRect | Self::Underline => true,
+            Self::Ellipse => (d.x / half.x).powi(2) + (d.y / half.y).powi(2) <= 1.0,
+            Self::Capsule => {
+                let r = half.y.min(half.x);
+                let dx = (d.x.abs() - (half.x - r)).max(0.0);
+                dx * dx + d.y * d.y <= r * r
+            }
+            Self::Diamond => d.x.abs() / half.x + d.y.abs() / half.y <= 1.0,
+        }
+    }
+
+    //外形的轮廓点（顺时针），椭圆用 segments 段折线近似；圆角矩形和胶囊返回矩形四个角
+    pub fn outline(self, rect: Rect, segments: usize) -> Vec<Point> {
+        let c = rect.center();
+        let half = rect.size() / 2.0;
+        match self {
+            Self::Ellipse => (0..segments.max(8))
+                .map(|i| {
+                    let angle = i as f32 / segments.max(8) as f32 * std::f32::consts::TAU;
+                    c + vector(half.x * angle.cos(), half.y * angle.sin())
+                })
+                .collect(),
+            Self::Diamond => vec![
+                c - vector(0.0, half.y),
+                c + vector(half.x, 0.0),
+                c + vector(0.0, half.y),
+                c - vector(half.x, 0.0),
+            ],
+            _ => vec![rect.min, Point::new(rect.max.x, rect.min.y), rect.max, Point::new(rect.min.x, rect.max.y)],
+        }
+    }
+}
+
+/// 节点大小跟随文字时的排版参数，单位都是世界坐标
+#[derive(Debug, Clone, Copy)]
+pub struct TextLayout {
+    /// 为 false 时保持 node_size 不变
+    pub auto_size: bool,
+    /// 文字超过这个宽度时自动换行
+    pub max_width: f32,
+    /// 文字四周的留白
+    pub padding: Vector,
+    /// 文字很短时节点的最小大小
+    pub min_size: Vector,
+}
+
+impl Default for TextLayout {
+    fn default() -> Self {
+        Self {
+            auto_size: true,
+            max_width: 240.0,
+            padding: vector(10.0, 8.0),
+            min_size: DEFAULT_NODE_SIZE,
+        }
+    }
+}
+
+impl TextLayout {
+    //排版后的文字大小加上留白，再按外形放大到能装下文字，不小于 min_size
+    pub fn node_size(&self, text_size: Vector, shape: NodeShape) -> Vector {
+        let size = shape.size_for_content(text_size + self.padding * 2.0);
+        vector(size.x.max(self.min_size.x), size.y.max(self.min_size.y))
+    }
+}
+
+/// 节点自己设置的样式，和选中、悬停的高亮无关；为 None 的属性使用默认样式
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NodeStyle {
+    /// 填充色，文件里沿用原来的 node_color 字段名
+    #[serde(rename = "node_color", skip_serializing_if = "Option::is_none")]
+    pub fill: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub border_color: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub border_width: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_color: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bold: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub italic: Option<bool>,
+    /// 分支颜色，覆盖自动分配的颜色，子节点和它们的连线都跟着使用
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch_color: Option<Color>,
+    /// 连线的走线方式，和分支颜色一样作用于自己和所有子孙节点到父节点的连线
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connector: Option<ConnectorKind>,
+}
+
+/// 所有属性都确定了的样式，用于绘制和导出
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResolvedStyle {
+    pub fill: Color,
+    pub border_color: Color,
+    /// 为 0 时不画边框
+    pub border_width: f32,
+    pub text_color: Color,
+    pub font_size: f32,
+    pub bold: bool,
+    pub italic: bool,
+}
+
+impl Default for ResolvedStyle {
+    fn default() -> Self {
+        Self {
+            fill: DEFAULT_NODE_COLOR,
+            border_color: Color::BLACK,
+            border_width: 0.0,
+            text_color: Color::BLACK,
+            font_size: DEFAULT_FONT_SIZE,
+            bold: false,
+            italic: false,
+        }
+    }
+}
+
+impl NodeStyle {
+    //只把 before 到 after 之间改过的属性应用到自己身上，用于同时修改多个样式不同的节点
+    pub fn apply_changes(&mut self, before: &NodeStyle, after: &NodeStyle) {
+        fn apply<T: PartialEq + Copy>(target: &mut Option<T>, before: Option<T>, after: Option<T>) {
+            if before != after {
+                *target = after;
+            }
+        }
+        apply(&mut self.fill, before.fill, after.fill);
+        apply(&mut self.border_color, before.border_color, after.border_color);
+        apply(&mut self.border_width, before.border_width, after.border_width);
+        apply(&mut self.text_color, before.text_color, after.text_color);
+        apply(&mut self.font_size, before.font_size, after.font_size);
+        apply(&mut self.bold, before.bold, after.bold);
+        apply(&mut self.italic, before.italic, after.italic);
+        apply(&mut self.branch_color, before.branch_color, after.branch_color);
+        apply(&mut self.connector, before.connector, after.connector);
+    }
+    //没设置的属性用 defaults 补上
+    pub fn resolve(&self, defaults: &ResolvedStyle) -> ResolvedStyle {
+        ResolvedStyle {
+            fill: self.fill.unwrap_or(defaults.fill),
+            border_color: self.border_color.unwrap_or(defaults.border_color),
+            border_width: self.border_width.unwrap_or(defaults.border_width),
+            text_color: self.text_color.unwrap_or(defaults.text_color),
+            font_size: self.font_size.unwrap_or(defaults.font_size),
+            bold: self.bold.unwrap_or(defaults.bold),
+            italic: self.italic.unwrap_or(defaults.italic),
+        }
+    }
+}
+
+/// 导图里的一个节点，只保存文档数据，不包含任何界面状态
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub struct Node{
+    pub node_id:NodeId,
+    pub node_pos: Point,//节点中心
+    pub node_size:Vector,
+    #[serde(flatten)]
+    pub node_style: NodeStyle,
+    pub node_text:String,
+    /// 节点的备注，Markdown 格式，导图上只显示一个图标
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+    pub father_id:Option<NodeId>,
+    #[serde(default)]
+    pub node_shape: NodeShape,
+    /// 显示在到父节点的连线中间的文字，比如"导致"、"是/否"
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub connector_label: String,
+    /// 把自己和子孙节点框起来的边界
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boundary: Option<Boundary>,
+    /// 节点上的超链接，在节点上显示为一个可以点击的图标
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hyperlink: Option<Hyperlink>,
+}
+impl Default for Node {
+    fn default() -> Self {
+        Self {
+            node_id:NodeId::default(),
+            node_pos: Point::new(100.0, 200.0),
+            node_size: DEFAULT_NODE_SIZE,
+            node_text : String::from("text"),
+            note: String::new(),
+            node_style: NodeStyle::default(),
+            father_id:None,
+            node_shape: NodeShape::default(),
+            connector_label: String::new(),
+            boundary: None,
+            hyperlink: None,
+        }
+    }
+}
+impl  Node{
+    //设置节点的填充色，None 表示使用默认颜色
+    pub fn set_color(&mut self, color: Option<Color>) {
+        self.node_style.fill = color;
+    }
+    //节点最终使用的样式
+    pub fn style(&self, defaults: &ResolvedStyle) -> ResolvedStyle {
+        self.node_style.resolve(defaults)
+    }
+    //设置节点位置
+    pub fn set_pos(&mut self,pos:Point){
+        self.node_pos=pos;
+    }
+    //节点在世界坐标下占据的矩形
+    pub fn rect(&self) -> Rect {
+        Rect::from_center_size(self.node_pos, self.node_size)
+    }
+    //连到父节点的连线的起点，在节点左侧；下划线样式在线的左端
+    pub fn left_anchor(&self) -> Point {
+        match self.node_shape {
+            NodeShape::Underline => self.node_pos + self.node_size * vector(-0.5, 0.5),
+            _ => self.node_pos - self.node_size * vector(0.5, 0.0),
+        }
+    }
+    //连到子节点的一侧，在节点右侧；下划线样式在线的右端
+    pub fn right_anchor(&self) -> Point {
+        match self.node_shape {
+            NodeShape::Underline => self.node_pos + self.node_size * vector(0.5, 0.5),
+            _ => self.node_pos + self.node_size * vector(0.5, 0.0),
+        }
+    }
+    //文字换行宽度：节点里留出边距后的宽度，不超过 max_width，画布和导出共用
+    pub fn text_wrap_width(&self, text_layout: &TextLayout) -> f32 {
+        (self.node_size.x - text_layout.padding.x * 2.0).min(text_layout.max_width)
+    }
+    //点是否在节点的外形上
+    pub fn contains(&self, p: Point) -> bool {
+        self.node_shape.contains(self.rect(), p)
+    }
+    // 展开按钮的位置，固定在节点右侧的连接点外
+    pub fn button_pos(&self) -> Point {
+        self.right_anchor() + vector(BUTTON_SIZE, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::geometry::point;
+
+    #[test]
+    fn hit_testing_follows_the_shape() {
+        let rect = Rect::from_min_size(point(0.0, 0.0), vector(100.0, 40.0));
+        //四个角只落在矩形类外形里
+        let corner = point(2.0, 2.0);
+        let edge_middle = point(99.0, 20.0);
+        for shape in NodeShape::ALL {
+            assert!(shape.contains(rect, rect.center()), "{}", shape.name());
+            assert!(shape.contains(rect, edge_middle), "{}", shape.name());
+            assert!(!shape.contains(rect, point(101.0, 20.0)), "{}", shape.name());
+            let rectangular = matches!(shape, NodeShape::RoundedRect | NodeShape::Underline);
+            assert_eq!(shape.contains(rect, corner), rectangular, "{}", shape.name());
+        }
+        //胶囊的直线段部分贴着上下边
+        assert!(NodeShape::Capsule.contains(rect, point(50.0, 1.0)));
+        assert!(!NodeShape::Diamond.contains(rect, point(50.0 + 30.0, 20.0 + 10.0)));
+    }
+
+    #[test]
+    fn outlines_stay_on_the_shape_border() {
+        let rect = Rect::from_center_size(point(10.0, -5.0), vector(80.0, 30.0));
+        let ellipse = NodeShape::Ellipse.outline(rect, 32);
+        assert_eq!(ellipse.len(), 32);
+        for p in ellipse {
+            let d = p - rect.center();
+            assert!(((d.x / 40.0).powi(2) + (d.y / 15.0).powi(2) - 1.0).abs() < 1e-4);
+        }
+        //段数太少时至少用 8 段
+        assert_eq!(NodeShape::Ellipse.outline(rect, 3).len(), 8);
+        assert_eq!(NodeShape::Diamond.outline(rect, 0)[1], point(50.0, -5.0));
+        assert_eq!(NodeShape::Capsule.outline(rect, 0), NodeShape::RoundedRect.outline(rect, 0));
+    }
+
+    #[test]
+    fn content_fits_inside_the_grown_shape() {
+        let content = vector(60.0, 20.0);
+        for shape in NodeShape::ALL {
+            let rect = Rect::from_center_size(point(0.0, 0.0), shape.size_for_content(content));
+            let inner = Rect::from_center_size(point(0.0, 0.0), content * 0.999);
+            for corner in [inner.min, inner.max, point(inner.min.x, inner.max.y), point(inner.max.x, inner.min.y)] {
+                assert!(shape.contains(rect, corner), "{}", shape.name());
+            }
+        }
+    }
+
+    #[test]
+    fn style_changes_only_touch_edited_properties() {
+        let mut node = NodeStyle { fill: Some(Color::WHITE), bold: Some(true), ..NodeStyle::default() };
+        //多选时面板显示的是第一个节点的样式，只改了字号
+        let before = NodeStyle { fill: Some(Color::BLACK), ..NodeStyle::default() };
+        let after = NodeStyle { font_size: Some(20.0), ..before };
+        node.apply_changes(&before, &after);
+        assert_eq!(node, NodeStyle { fill: Some(Color::WHITE), bold: Some(true), font_size: Some(20.0), ..NodeStyle::default() });
+        //改回默认值时清掉节点自己的设置
+        node.apply_changes(&after, &before);
+        assert_eq!(node.font_size, None);
+    }
+
+    #[test]
+    fn unset_properties_fall_back_to_defaults() {
+        let defaults = ResolvedStyle { border_width: 2.0, ..ResolvedStyle::default() };
+        assert_eq!(NodeStyle::default().resolve(&defaults), defaults);
+        let style = NodeStyle { text_color: Some(Color::WHITE), italic: Some(true), ..NodeStyle::default() };
+        let resolved = style.resolve(&defaults);
+        assert_eq!(resolved, ResolvedStyle { text_color: Color::WHITE, italic: true, ..defaults });
+        //旧文件里的 node_color 字段读成填充色
+        let style: NodeStyle = serde_json::from_str(r#"{"node_color":[1,2,3,255]}"#).unwrap();
+        assert_eq!(style.fill, Some(Color::from_rgb(1, 2, 3)));
+    }
+
+    #[test]
+    fn auto_size_grows_with_text_but_not_below_the_minimum() {
+        let layout = TextLayout::default();
+        assert_eq!(layout.node_size(vector(10.0, 10.0), NodeShape::RoundedRect), layout.min_size);
+        let text = vector(200.0, 60.0);
+        assert_eq!(layout.node_size(text, NodeShape::RoundedRect), vector(220.0, 76.0));
+        assert_eq!(layout.node_size(text, NodeShape::Capsule), vector(296.0, 76.0));
+        //换行宽度去掉左右留白，再宽的节点也不超过 max_width
+        let mut node = Node { node_size: vector(120.0, 50.0), ..Node::default() };
+        assert_eq!(node.text_wrap_width(&layout), 100.0);
+        node.node_size.x = 1000.0;
+        assert_eq!(node.text_wrap_width(&layout), layout.max_width);
+    }
+}
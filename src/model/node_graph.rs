@@ -0,0 +1,582 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+use slotmap::{SecondaryMap, SlotMap};
+use serde::{Deserialize, Serialize};
+use super::connector::ConnectorStyle;
+use super::cross_link::{CrossLink, LinkId};
+use super::summary::{Summary, SummaryId};
+use super::geometry::{Color, Point, Rect, Vector, point, vector};
+use super::node::{Node, NodeShape, NodeStyle, ResolvedStyle, BUTTON_SIZE, DEFAULT_NODE_SIZE};
+use super::theme::Theme;
+use super::tree::TreeInfo;
+
+
+slotmap::new_key_type! {
+    pub struct NodeId;
+}
+
+//node_id 要作为 area id的唯一值，需要实现display trait
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "node_{:?}", self.0)
+    }
+}
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct GraphStats {
+    pub nodes: usize,
+    pub roots: usize,
+    pub leaves: usize,
+    pub max_depth: usize,
+    pub links: usize,
+}
+
+/// 导图文档：节点组成的森林
+///
+/// 通过下面的方法修改时会保持这些不变量：
+/// - 每个节点的 `node_id` 等于它在 `nodes` 里的 key
+/// - `father_id` 指向的节点一定存在
+/// - 父子关系没有环
+/// - `children` 索引和每个节点的 `father_id` 一致
+/// - 每条关系连线 `links` 两端的节点都存在
+/// - 每个概要 `summaries` 括起来的节点和概要节点都存在，删除其中任何一个时概要和概要节点一起删除
+///
+/// 直接修改 `father_id` 或往 `nodes` 里插入节点后，需要调用 `rebuild_children_index`，
+/// 直接修改 `summaries` 后需要调用 `rebuild_topic_index`；
+/// 直接修改节点的样式或边界后需要调用 `touch`，让按版本缓存的 `tree_info`、边界外框和缩略图失效
+#[derive(Default,Serialize,Deserialize)]
+#[serde(from = "NodeGraphData")]
+pub struct NodeGraph {
+    pub nodes: SlotMap<NodeId, Node>,
+    pub last_key:Option<NodeId>,
+    //文档使用的主题，节点没有设置的样式从这里取
+    pub theme: Theme,
+    //树结构以外的关系连线
+    pub links: SlotMap<LinkId, CrossLink>,
+    //括起几个兄弟节点的概要
+    pub summaries: SlotMap<SummaryId, Summary>,
+    //父节点 -> 子节点列表（按插入顺序），由 nodes 推导，不保存到文件
+    #[serde(skip_serializing)]
+    children: SecondaryMap<NodeId, Vec<NodeId>>,
+    //概要节点 -> 所属的概要，由 summaries 推导，不保存到文件
+    #[serde(skip_serializing)]
+    pub(super) topics: SecondaryMap<NodeId, SummaryId>,
+    //结构或样式每修改一次加一，缓存用它判断是否过期
+    #[serde(skip_serializing)]
+    revision: u64,
+    //按版本缓存的层级等树信息
+    #[serde(skip_serializing)]
+    pub(super) tree_cache: RefCell<Option<Rc<TreeInfo>>>,
+}
+
+//文件里保存的字段，读取后重建子节点索引
+#[derive(Deserialize)]
+struct NodeGraphData {
+    nodes: SlotMap<NodeId, Node>,
+    last_key: Option<NodeId>,
+    #[serde(default)]
+    theme: Theme,
+    #[serde(default)]
+    links: SlotMap<LinkId, CrossLink>,
+    #[serde(default)]
+    summaries: SlotMap<SummaryId, Summary>,
+}
+
+impl From<NodeGraphData> for NodeGraph {
+    fn from(data: NodeGraphData) -> Self {
+        let mut graph = NodeGraph { nodes: data.nodes, last_key: data.last_key, theme: data.theme, links: data.links, summaries: data.summaries, ..NodeGraph::default() };
+        graph.rebuild_children_index();
+        graph.rebuild_topic_index();
+        graph.remove_dangling_links();
+        for topic in graph.remove_dangling_summaries() {
+            graph.remove_node(topic);
+        }
+        graph.layout_summaries();
+        graph
+    }
+}
+
+impl NodeGraph{
+    //在指定位置新增根节点
+    pub fn add_node_with_position(&mut self,pos2: Point)->NodeId {
+        let nodeid=self.nodes.insert_with_key(|node_id| {
+            Node {
+                node_id,
+                node_pos: pos2,
+                node_size: DEFAULT_NODE_SIZE,
+                node_text: String::from("text"),
+                note: String::new(),
+                node_style: NodeStyle::default(),
+                father_id: None,
+                node_shape: NodeShape::default(),
+                connector_label: String::new(),
+                boundary: None,
+                hyperlink: None,
+            }
+        });
+        self.last_key=Some(nodeid);
+        self.touch();
+        nodeid
+    }
+    //给父节点新增子节点
+    pub fn add_node_with_father_node(&mut self, father_node_id:NodeId)->NodeId{
+        let node_pos=self.nodes[father_node_id].node_pos+vector(300.0,0.0);//默认往右平移300，后面需要算法计算位置
+        let node_shape=self.nodes[father_node_id].node_shape;//子节点沿用父节点的外形
+
+        let nodeid=self.nodes.insert_with_key(|node_id| {
+            Node {
+                node_id,
+                node_pos,
+                node_size: DEFAULT_NODE_SIZE,
+                node_text: String::from("text"),
+                note: String::new(),
+                node_style: NodeStyle::default(),
+                father_id: Some(father_node_id),
+                node_shape,
+                connector_label: String::new(),
+                boundary: None,
+                hyperlink: None,
+            }
+        });
+        self.last_key=Some(nodeid);
+        self.link_child(father_node_id, nodeid);
+        nodeid
+    }
+    //根据每个节点的 father_id 重新建立子节点索引
+    pub fn rebuild_children_index(&mut self) {
+        self.touch();
+        self.children.clear();
+        let links: Vec<(NodeId, NodeId)> = self.nodes.values()
+            .filter_map(|node| node.father_id.map(|father_id| (father_id, node.node_id)))
+            .collect();
+        for (father_id, node_id) in links {
+            self.link_child(father_id, node_id);
+        }
+    }
+    fn link_child(&mut self, father_id: NodeId, node_id: NodeId) {
+        self.touch();
+        if let Some(entry) = self.children.entry(father_id) {
+            entry.or_default().push(node_id);
+        }
+    }
+    fn unlink_child(&mut self, father_id: NodeId, node_id: NodeId) {
+        self.touch();
+        if let Some(children) = self.children.get_mut(father_id) {
+            children.retain(|id| *id != node_id);
+        }
+    }
+    //删除节点和它的所有子节点，返回被删除的节点
+    pub fn remove_node(&mut self, node_id: NodeId) -> Vec<NodeId> {
+        if !self.nodes.contains_key(node_id) {
+            return Vec::new();
+        }
+        let mut removed = self.query_all_children_nodes(node_id);
+        removed.push(node_id);
+        self.touch();
+        if let Some(father_id) = self.nodes[node_id].father_id {
+            self.unlink_child(father_id, node_id);
+        }
+        for id in &removed {
+            self.nodes.remove(*id);
+            self.children.remove(*id);
+        }
+        self.remove_dangling_links();
+        //括起来的节点没了的概要，连同概要节点一起删除
+        for topic in self.remove_dangling_summaries() {
+            removed.extend(self.remove_node(topic));
+        }
+        if self.last_key.is_some_and(|key| !self.nodes.contains_key(key)) {
+            self.last_key = None;
+        }
+        removed
+    }
+    pub fn set_text(&mut self, node_id: NodeId, text: impl Into<String>) {
+        if let Some(node) = self.nodes.get_mut(node_id) {
+            node.node_text = text.into();
+        }
+    }
+    //文字或备注里包含 query 的节点（不区分大小写），按 id 排序；query 为空时没有结果
+    pub fn search(&self, query: &str) -> Vec<NodeId> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let mut found: Vec<NodeId> = self.nodes.iter()
+            .filter(|(_, node)| node.node_text.to_lowercase().contains(&query) || node.note.to_lowercase().contains(&query))
+            .map(|(id, _)| id)
+            .collect();
+        found.sort_unstable();
+        found
+    }
+    //设置节点到父节点连线上的文字，空字符串表示不显示
+    pub fn set_connector_label(&mut self, node_id: NodeId, label: impl Into<String>) {
+        if let Some(node) = self.nodes.get_mut(node_id) {
+            node.connector_label = label.into();
+        }
+    }
+    pub fn set_position(&mut self, node_id: NodeId, pos: Point) {
+        if let Some(node) = self.nodes.get_mut(node_id) {
+            node.set_pos(pos);
+        }
+    }
+    pub fn set_style(&mut self, node_id: NodeId, style: NodeStyle) {
+        if let Some(node) = self.nodes.get_mut(node_id) {
+            node.node_style = style;
+            self.revision += 1;
+        }
+    }
+    //导图的版本，结构、样式、边界或概要修改后会变
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+    //直接修改了节点的父节点、样式或边界后调用，让按版本缓存的信息失效
+    pub fn touch(&mut self) {
+        self.revision += 1;
+    }
+    pub fn set_shape(&mut self, node_id: NodeId, shape: NodeShape) {
+        if let Some(node) = self.nodes.get_mut(node_id) {
+            node.node_shape = shape;
+        }
+    }
+    //平移节点，子节点不跟着移动
+    pub fn move_node(&mut self, node_id: NodeId, delta: Vector) {
+        if let Some(node) = self.nodes.get_mut(node_id) {
+            node.node_pos += delta;
+        }
+    }
+    //修改父节点，新的父节点不能是自己或自己的子节点
+    pub fn set_father(&mut self, node_id: NodeId, father_id: Option<NodeId>) -> Result<(), String> {
+        if !self.nodes.contains_key(node_id) {
+            return Err(format!("{node_id} does not exist"));
+        }
+        if let Some(father_id) = father_id {
+            if !self.nodes.contains_key(father_id) {
+                return Err(format!("{father_id} does not exist"));
+            }
+            if self.is_same_or_descendant(father_id, node_id) {
+                return Err(format!("{father_id} is {node_id} or one of its children"));
+            }
+        }
+        let old_father_id = self.nodes[node_id].father_id;
+        if old_father_id == father_id {
+            return Ok(());
+        }
+        //换了父节点后原来的概要范围会跨两个父节点，先缩小或去掉
+        let topics = self.detach_from_summaries(node_id);
+        if let Some(old_father_id) = old_father_id {
+            self.unlink_child(old_father_id, node_id);
+        }
+        self.nodes[node_id].father_id = father_id;
+        if let Some(father_id) = father_id {
+            self.link_child(father_id, node_id);
+        }
+        for topic in topics {
+            //节点被挂到了概要节点下面时保留概要节点，免得把它一起删掉
+            if !self.is_same_or_descendant(node_id, topic) {
+                self.remove_node(topic);
+            }
+        }
+        self.layout_summaries();
+        Ok(())
+    }
+    //node_id 是否就是 ancestor_id 或在它的子树里，沿父节点向上找，只需要走节点的深度
+    pub fn is_same_or_descendant(&self, node_id: NodeId, ancestor_id: NodeId) -> bool {
+        let mut current = Some(node_id);
+        let mut steps = 0;
+        while let Some(id) = current {
+            if id == ancestor_id {
+                return true;
+            }
+            current = self.nodes.get(id).and_then(|node| node.father_id);
+            steps += 1;
+            if steps > self.nodes.len() {
+                break;
+            }
+        }
+        false
+    }
+    //下一级子节点，直接读索引
+    pub fn children(&self, father_node_id: NodeId) -> &[NodeId] {
+        self.children.get(father_node_id).map_or(&[], Vec::as_slice)
+    }
+    pub fn has_children(&self, node_id: NodeId) -> bool {
+        !self.children(node_id).is_empty()
+    }
+    //所有有子节点的节点
+    pub fn nodes_with_children(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.children.iter().filter(|(_, children)| !children.is_empty()).map(|(id, _)| id)
+    }
+    //查询下一级子节点合集
+    pub fn query_next_children_nodes(&self,father_node_id:NodeId) -> Vec<NodeId>{
+        self.children(father_node_id).to_vec()
+    }
+    //查询所有子节点合集，时间和子树大小成正比
+    pub fn query_all_children_nodes(&self, father_node_id: NodeId) -> Vec<NodeId> {
+        let mut nodes_all_children = Vec::new();
+
+        // 用一个栈结构模拟递归过程
+        let mut stack = self.query_next_children_nodes(father_node_id);  // 初始化栈，加入所有直接子节点
+
+        // 迭代查找每个子节点的子节点
+        while let Some(child_node_id) = stack.pop() {
+            nodes_all_children.push(child_node_id);
+            // 将子节点的下一级子节点加入到栈中，继续处理
+            stack.extend_from_slice(self.children(child_node_id));
+            if nodes_all_children.len() > self.nodes.len() {
+                break; //有环，validate 会报告
+            }
+        }
+
+        nodes_all_children
+    }
+    //查询所有根节点，按位置从上到下排列；概要节点虽然没有父节点，但不算根节点
+    pub fn root_nodes(&self) -> Vec<NodeId> {
+        let mut roots: Vec<NodeId> = self.nodes.values()
+            .filter(|node| node.father_id.is_none() && self.summary_of_topic(node.node_id).is_none())
+            .map(|node| node.node_id)
+            .collect();
+        self.sort_by_position(&mut roots);
+        roots
+    }
+    //查询下一级子节点，按位置从上到下排列，导出时用这个顺序
+    pub fn ordered_children(&self, father_node_id: NodeId) -> Vec<NodeId> {
+        let mut children = self.query_next_children_nodes(father_node_id);
+        self.sort_by_position(&mut children);
+        children
+    }
+    fn sort_by_position(&self, ids: &mut [NodeId]) {
+        ids.sort_by(|a, b| {
+            let (a, b) = (self.nodes[*a].node_pos, self.nodes[*b].node_pos);
+            a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x))
+        });
+    }
+    //节点的层级，根节点为 1，从缓存的 `tree_info` 里读；父节点链上有环时大于节点数
+    pub fn node_depth(&self, node_id: NodeId) -> usize {
+        if let Some(depth) = self.tree_info().depth.get(node_id) {
+            return *depth;
+        }
+        let mut depth = 0;
+        let mut current = Some(node_id);
+        while let Some(id) = current {
+            depth += 1;
+            current = self.nodes.get(id).and_then(|node| node.father_id);
+            if depth > self.nodes.len() {
+                break; //有环，validate 会报告
+            }
+        }
+        depth
+    }
+    //从节点自己开始往上直到根节点，有环时最多走节点数那么多步
+    pub fn ancestors_and_self(&self, node_id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(Some(node_id), |id| self.nodes.get(*id).and_then(|node| node.father_id))
+            .take(self.nodes.len())
+    }
+    //节点在指定层级上的祖先，节点本身的层级不够深时返回 None
+    pub fn ancestor_at_depth(&self, node_id: NodeId, depth: usize) -> Option<NodeId> {
+        let node_depth = self.node_depth(node_id);
+        if depth == 0 || node_depth < depth {
+            return None;
+        }
+        let mut current = node_id;
+        for _ in depth..node_depth {
+            current = self.nodes.get(current)?.father_id?;
+        }
+        Some(current)
+    }
+    //主题里节点所在层级的默认样式，有分支颜色时按分支颜色调整
+    pub fn default_style(&self, node_id: NodeId) -> ResolvedStyle {
+        //概要节点和它括起来的节点用一样的默认样式
+        if let Some(summary) = self.summary_of_topic(node_id).and_then(|id| self.summaries.get(id)) {
+            return self.default_style(summary.from);
+        }
+        let depth = self.node_depth(node_id);
+        match self.tree_info().branch_color.get(node_id).copied() {
+            Some(color) => self.theme.branch_style(depth, color),
+            None => self.theme.node_style(depth),
+        }
+    }
+    //节点所在分支的颜色：自己或最近的祖先设置的 branch_color；
+    //都没有设置且主题开启了自动分支颜色时，按一级分支在根节点子节点里的顺序从调色板取；
+    //概要节点沿用括起来的节点的颜色，结果见 `TreeInfo::branch_color`
+    pub fn branch_color(&self, node_id: NodeId) -> Option<Color> {
+        self.tree_info().branch_color.get(node_id).copied()
+    }
+    //连到父节点的连线颜色：分支颜色，没有时用主题的连线颜色
+    pub fn connector_color(&self, node_id: NodeId) -> Color {
+        self.branch_color(node_id).unwrap_or(self.theme.connector.color)
+    }
+    //连到父节点的连线样式：主题的连线样式，颜色用分支颜色，走线方式用自己或最近的祖先设置的 connector
+    pub fn connector_style(&self, node_id: NodeId) -> ConnectorStyle {
+        let tree = self.tree_info();
+        let kind = tree.connector_kind.get(node_id).copied().unwrap_or(self.theme.connector.kind);
+        let color = tree.branch_color.get(node_id).copied().unwrap_or(self.theme.connector.color);
+        ConnectorStyle { color, kind, ..self.theme.connector }
+    }
+    //节点最终使用的样式：自己设置的属性加上主题的默认样式
+    pub fn node_style(&self, node_id: NodeId) -> ResolvedStyle {
+        let defaults = self.default_style(node_id);
+        self.nodes.get(node_id).map_or(defaults, |node| node.style(&defaults))
+    }
+    //所有节点的包围盒，没有节点时返回 None
+    pub fn bounds(&self) -> Option<Rect> {
+        self.bounds_of(self.nodes.keys())
+    }
+    //指定节点的包围盒
+    pub fn bounds_of(&self, ids: impl IntoIterator<Item = NodeId>) -> Option<Rect> {
+        let bounds = ids.into_iter()
+            .filter_map(|id| self.nodes.get(id))
+            .fold(Rect::NOTHING, |bounds, node| bounds.union(node.rect()));
+        (!bounds.is_negative()).then_some(bounds)
+    }
+    //统计信息
+    pub fn stats(&self) -> GraphStats {
+        let mut stats = GraphStats { nodes: self.nodes.len(), links: self.links.len(), ..GraphStats::default() };
+        stats.max_depth = self.tree_info().depth.values().copied().max().unwrap_or(0);
+        for node in self.nodes.values() {
+            if node.father_id.is_none() && self.summary_of_topic(node.node_id).is_none() {
+                stats.roots += 1;
+            }
+            if !self.has_children(node.node_id) {
+                stats.leaves += 1;
+            }
+        }
+        stats
+    }
+    //检查导图的结构是否满足不变量，返回发现的所有问题
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        //从根节点往下遍历不到的节点在父节点的环上
+        let tree = self.tree_info();
+        for (key, node) in &self.nodes {
+            if node.node_id != key {
+                problems.push(format!("{key} stores mismatched id {}", node.node_id));
+            }
+            if let Some(father_id) = node.father_id {
+                if !self.nodes.contains_key(father_id) {
+                    problems.push(format!("{key} refers to missing father {father_id}"));
+                } else if !tree.depth.contains_key(key) {
+                    problems.push(format!("{key} is part of a father cycle"));
+                }
+            }
+        }
+        for summary in self.summaries.values() {
+            let father = |id| self.nodes.get(id).map(|node: &Node| node.father_id);
+            if father(summary.from).is_none() || father(summary.to).is_none() || !self.nodes.contains_key(summary.topic) {
+                problems.push(format!("a summary refers to missing node {} {} or {}", summary.from, summary.to, summary.topic));
+            } else if father(summary.from) != father(summary.to) {
+                problems.push(format!("summary range {} .. {} spans different fathers", summary.from, summary.to));
+            }
+        }
+        for key in self.broken_links() {
+            problems.push(format!("{key} has a broken hyperlink"));
+        }
+        for link in self.links.values() {
+            for end in [link.source, link.target] {
+                if !self.nodes.contains_key(end) {
+                    problems.push(format!("a link refers to missing node {end}"));
+                }
+            }
+        }
+        problems
+    }
+}
+
+//子节点到父节点展开按钮的贝塞尔曲线控制点，绘制和导出共用
+pub fn curve_control_points(node: &Node, father_node: &Node) -> [Point; 4] {
+    let father_button_pos = father_node.button_pos();
+    let anchor = node.left_anchor();
+    [
+        anchor,
+        point((node.node_pos.x + father_button_pos.x) / 2.0, anchor.y),
+        point((node.node_pos.x + father_button_pos.x) / 2.0, father_button_pos.y),
+        father_button_pos + vector(BUTTON_SIZE, BUTTON_SIZE) * vector(0.5, 0.0),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //一个根节点和两个子节点，第一个子节点下面还有一个子节点
+    fn sample_graph() -> (NodeGraph, [NodeId; 4]) {
+        let mut graph = NodeGraph::default();
+        let root = graph.add_node_with_position(Point::ZERO);
+        let a = graph.add_node_with_father_node(root);
+        let b = graph.add_node_with_father_node(root);
+        let c = graph.add_node_with_father_node(a);
+        (graph, [root, a, b, c])
+    }
+
+    //子节点索引和 father_id 一致
+    fn assert_index_consistent(graph: &NodeGraph) {
+        for (id, node) in &graph.nodes {
+            if let Some(father_id) = node.father_id {
+                assert_eq!(graph.children(father_id).iter().filter(|child| **child == id).count(), 1);
+            }
+            for child in graph.children(id) {
+                assert_eq!(graph.nodes[*child].father_id, Some(id));
+            }
+        }
+    }
+
+    #[test]
+    fn children_index_follows_changes() {
+        let (mut graph, [root, a, b, c]) = sample_graph();
+        assert_eq!(graph.children(root), &[a, b]);
+        assert_eq!(graph.children(a), &[c]);
+        graph.set_father(c, Some(b)).unwrap();
+        assert!(graph.children(a).is_empty());
+        assert_eq!(graph.children(b), &[c]);
+        graph.set_father(b, None).unwrap();
+        assert_eq!(graph.children(root), &[a]);
+        assert_index_consistent(&graph);
+        assert_eq!(graph.remove_node(b), vec![c, b]);
+        assert!(graph.children(b).is_empty());
+        assert_index_consistent(&graph);
+        assert!(graph.validate().is_empty());
+    }
+
+    #[test]
+    fn set_father_rejects_cycles() {
+        let (mut graph, [root, a, _, c]) = sample_graph();
+        assert!(graph.set_father(root, Some(c)).is_err());
+        assert!(graph.set_father(a, Some(a)).is_err());
+        assert_eq!(graph.nodes[root].father_id, None);
+        assert_eq!(graph.nodes[a].father_id, Some(root));
+        assert_index_consistent(&graph);
+    }
+
+    #[test]
+    fn loading_rebuilds_index_and_drops_dangling_references() {
+        let (mut graph, [root, a, b, c]) = sample_graph();
+        graph.add_link(c, b).unwrap();
+        graph.add_link(a, root).unwrap();
+        graph.add_summary(a, b).unwrap();
+        //绕过 remove_node 直接删掉节点，模拟不一致的文件
+        graph.nodes.remove(c);
+        graph.nodes.remove(b);
+        let src = serde_json::to_string(&graph).unwrap();
+        let loaded: NodeGraph = serde_json::from_str(&src).unwrap();
+        assert_eq!(loaded.links.len(), 1);
+        assert!(loaded.summaries.is_empty());
+        assert_eq!(loaded.nodes.len(), 2, "the summary topic is removed with its summary");
+        assert_eq!(loaded.children(root), &[a]);
+        assert_index_consistent(&loaded);
+        assert!(loaded.validate().is_empty());
+    }
+
+    #[test]
+    fn validate_reports_broken_structure() {
+        let (mut graph, [root, a, ..]) = sample_graph();
+        graph.nodes[root].father_id = Some(a);
+        graph.rebuild_children_index();
+        assert!(graph.validate().iter().any(|problem| problem.contains("cycle")));
+    }
+
+    #[test]
+    fn stats_count_tree_shape() {
+        let (graph, _) = sample_graph();
+        let stats = graph.stats();
+        assert_eq!(stats, GraphStats { nodes: 4, roots: 1, leaves: 2, max_depth: 3, links: 0 });
+    }
+}
@@ -0,0 +1,315 @@
+//! 节点文字里的一小部分 Markdown 行内格式：**粗体**、*斜体*、***粗斜体***、`代码`、~~删除线~~ 和 [链接](url)
+//!
+//! 只解析单行里的格式，不跨行；文档里保存的始终是原始文字，编辑时也编辑原始文字
+
+/// 格式相同的一段文字
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextSpan {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub code: bool,
+    pub strikethrough: bool,
+    /// 链接地址，链接文字本身也可以带格式
+    pub link: Option<String>,
+}
+
+impl TextSpan {
+    fn same_format(&self, other: &TextSpan) -> bool {
+        self.bold == other.bold
+            && self.italic == other.italic
+            && self.code == other.code
+            && self.strikethrough == other.strikethrough
+            && self.link == other.link
+    }
+}
+
+//可以用反斜杠转义的字符
+const ESCAPABLE: &str = "\\`*_~[]()";
+
+/// 按行解析格式，每行一组文字段
+pub fn parse_lines(text: &str) -> Vec<Vec<TextSpan>> {
+    text.split('\n').map(parse_inline).collect()
+}
+
+/// 解析一行文字的行内格式，没有配对的标记原样保留
+pub fn parse_inline(line: &str) -> Vec<TextSpan> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut spans = Vec::new();
+    parse_range(&chars, &TextSpan::default(), &mut spans);
+    spans
+}
+
+/// 去掉格式标记后的文字，用于不支持格式的导出和测量
+pub fn plain_text(text: &str) -> String {
+    parse_lines(text)
+        .iter()
+        .map(|spans| spans.iter().map(|span| span.text.as_str()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 把一行文字段按宽度折成多行，advance 是每个字符的宽度；在空白处换行，单词比一行还长时从中间断开，
+/// 行尾的空白去掉。和画布上 egui 的换行规则一致，导出时用
+pub fn wrap_spans(spans: &[TextSpan], max_width: f32, advance: impl Fn(char) -> f32) -> Vec<Vec<TextSpan>> {
+    //每个字符和它所在的文字段
+    let chars: Vec<(char, &TextSpan)> = spans.iter().flat_map(|span| span.text.chars().map(move |c| (c, span))).collect();
+    let mut breaks = Vec::new();
+    let mut start = 0;
+    let mut width = 0.0;
+    let mut last_space = None;
+    for (i, &(c, _)) in chars.iter().enumerate() {
+        let w = advance(c);
+        if width + w > max_width && i > start && !c.is_whitespace() {
+            let end = last_space.filter(|space| *space >= start).map_or(i, |space| space + 1);
+            breaks.push(start..end);
+            start = end;
+            width = chars[start..i].iter().map(|(c, _)| advance(*c)).sum();
+            last_space = None;
+        }
+        if c.is_whitespace() {
+            last_space = Some(i);
+        }
+        width += w;
+    }
+    breaks.push(start..chars.len());
+    breaks.into_iter().map(|range| {
+        let line = &chars[range];
+        let end = line.iter().rposition(|(c, _)| !c.is_whitespace()).map_or(0, |i| i + 1);
+        let mut wrapped = Vec::new();
+        for &(c, format) in &line[..end] {
+            push_char(&mut wrapped, format, c);
+        }
+        wrapped
+    }).collect()
+}
+
+fn parse_range(chars: &[char], format: &TextSpan, spans: &mut Vec<TextSpan>) {
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\\' && chars.get(i + 1).is_some_and(|next| ESCAPABLE.contains(*next)) {
+            push_char(spans, format, chars[i + 1]);
+            i += 2;
+            continue;
+        }
+        if c == '`' {
+            let run = backtick_run(chars, i);
+            match find_code_end(chars, i) {
+                Some(end) => {
+                    let code = TextSpan { code: true, ..format.clone() };
+                    for &c in trim_code(&chars[i + run..end]) {
+                        push_char(spans, &code, c);
+                    }
+                    i = end + run;
+                }
+                //没有配对的一串反引号整个原样保留，不会和后面更短的一串配对
+                None => {
+                    for &c in &chars[i..i + run] {
+                        push_char(spans, format, c);
+                    }
+                    i += run;
+                }
+            }
+            continue;
+        }
+        if c == '[' {
+            if let Some((text_end, url_end)) = find_link(chars, i) {
+                let url: String = chars[text_end + 2..url_end].iter().collect();
+                let link = TextSpan { link: Some(url.trim().to_owned()), ..format.clone() };
+                parse_range(&chars[i + 1..text_end], &link, spans);
+                i = url_end + 1;
+                continue;
+            }
+        }
+        if matches!(c, '*' | '_' | '~') {
+            let run = chars[i..].iter().take_while(|d| **d == c).count();
+            //~ 只有成对的删除线；三个 * 或 _ 是粗斜体
+            let len = if c == '~' { 2 } else { run.min(3) };
+            if run >= len {
+                if let Some(end) = find_closing(chars, i, c, len) {
+                    let inner = match (c, len) {
+                        ('~', _) => TextSpan { strikethrough: true, ..format.clone() },
+                        (_, 3) => TextSpan { bold: true, italic: true, ..format.clone() },
+                        (_, 2) => TextSpan { bold: true, ..format.clone() },
+                        _ => TextSpan { italic: true, ..format.clone() },
+                    };
+                    parse_range(&chars[i + len..end], &inner, spans);
+                    i = end + len;
+                    continue;
+                }
+            }
+        }
+        push_char(spans, format, c);
+        i += 1;
+    }
+}
+
+fn push_char(spans: &mut Vec<TextSpan>, format: &TextSpan, c: char) {
+    match spans.last_mut() {
+        Some(last) if last.same_format(format) => last.text.push(c),
+        _ => spans.push(TextSpan { text: c.to_string(), ..format.clone() }),
+    }
+}
+
+//从 start 开始连续的反引号个数
+fn backtick_run(chars: &[char], start: usize) -> usize {
+    chars[start..].iter().take_while(|c| **c == '`').count()
+}
+
+//start 处一串反引号开始的代码，返回结束的那串反引号的位置；两串反引号的个数要相同，
+//所以 ``a`b`` 里的代码是 a`b，代码不能为空
+fn find_code_end(chars: &[char], start: usize) -> Option<usize> {
+    let run = backtick_run(chars, start);
+    let mut j = start + run;
+    while j < chars.len() {
+        if chars[j] == '`' {
+            let len = backtick_run(chars, j);
+            if len == run && j > start + run {
+                return Some(j);
+            }
+            j += len;
+        } else {
+            j += 1;
+        }
+    }
+    None
+}
+
+//代码两头都有空格时各去掉一个，这样 `` `a` `` 可以写出带反引号的代码
+fn trim_code(code: &[char]) -> &[char] {
+    match code {
+        [' ', inner @ .., ' '] if inner.iter().any(|c| *c != ' ') => inner,
+        _ => code,
+    }
+}
+
+//start 处 "[text](url)" 的 ']' 和 ')' 的位置
+fn find_link(chars: &[char], start: usize) -> Option<(usize, usize)> {
+    let text_end = (start + 1..chars.len()).find(|&j| chars[j] == ']')?;
+    if text_end == start + 1 || chars.get(text_end + 1) != Some(&'(') {
+        return None;
+    }
+    let url_end = (text_end + 2..chars.len()).find(|&j| chars[j] == ')')?;
+    (url_end > text_end + 2).then_some((text_end, url_end))
+}
+
+//start 处 len 个 delimiter 组成的开始标记对应的结束标记位置
+//开始标记后和结束标记前不能是空白；下划线不能在单词中间，免得把 snake_case 当成斜体
+fn find_closing(chars: &[char], start: usize, delimiter: char, len: usize) -> Option<usize> {
+    let word = |j: Option<usize>| j.and_then(|j| chars.get(j)).is_some_and(|c| c.is_alphanumeric());
+    let first = *chars.get(start + len)?;
+    if first.is_whitespace() || first == delimiter {
+        return None;
+    }
+    if delimiter == '_' && word(start.checked_sub(1)) {
+        return None;
+    }
+    //开始标记后第一个字符也可能是代码的开头，从它开始找
+    let mut j = start + len;
+    while j < chars.len() {
+        match chars[j] {
+            '\\' => j += 1,
+            //代码里的标记不算结束标记
+            '`' => {
+                let run = backtick_run(chars, j);
+                j = find_code_end(chars, j).unwrap_or(j) + run - 1;
+            }
+            c if c == delimiter => {
+                let run = chars[j..].iter().take_while(|c| **c == delimiter).count();
+                let closes = run == len
+                    && !chars[j - 1].is_whitespace()
+                    && !(delimiter == '_' && word(Some(j + len)));
+                if closes {
+                    return Some(j);
+                }
+                j += run - 1;
+            }
+            _ => {}
+        }
+        j += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //把每段文字写成 "格式:文字"，格式里 b 粗体、i 斜体、c 代码、s 删除线、@url 链接，段之间用 | 隔开
+    fn describe(line: &str) -> String {
+        parse_inline(line).iter().map(|span| {
+            let flags = [(span.bold, "b"), (span.italic, "i"), (span.code, "c"), (span.strikethrough, "s")];
+            let mut format: String = flags.iter().filter(|(on, _)| *on).map(|(_, flag)| *flag).collect();
+            if let Some(url) = &span.link {
+                format.push('@');
+                format.push_str(url);
+            }
+            format!("{format}:{}", span.text)
+        }).collect::<Vec<_>>().join("|")
+    }
+
+    #[test]
+    fn parses_basic_markers() {
+        assert_eq!(describe("plain"), ":plain");
+        assert_eq!(describe("a **b** *c* _d_ ~~e~~"), ":a |b:b|: |i:c|: |i:d|: |s:e");
+        assert_eq!(describe("***both***"), "bi:both");
+        assert_eq!(describe("___both___"), "bi:both");
+    }
+
+    #[test]
+    fn parses_nested_markers() {
+        assert_eq!(describe("**bold *italic* bold**"), "b:bold |bi:italic|b: bold");
+        assert_eq!(describe("*italic **bold** italic*"), "i:italic |bi:bold|i: italic");
+        assert_eq!(describe("~~**gone**~~"), "bs:gone");
+    }
+
+    #[test]
+    fn keeps_unmatched_and_escaped_markers() {
+        assert_eq!(describe("**open"), ":**open");
+        assert_eq!(describe("a * b * c"), ":a * b * c");
+        assert_eq!(describe("~single~"), ":~single~");
+        assert_eq!(describe(r"\*not italic\*"), ":*not italic*");
+        assert_eq!(describe(r"**a\*\*b**"), "b:a**b");
+        assert_eq!(describe(r"\`not code\`"), ":`not code`");
+    }
+
+    #[test]
+    fn ignores_underscores_inside_words() {
+        assert_eq!(describe("snake_case_name"), ":snake_case_name");
+        assert_eq!(describe("call my_function_name _now_"), ":call my_function_name |i:now");
+    }
+
+    #[test]
+    fn parses_links() {
+        assert_eq!(describe("see [docs](https://example.com)"), ":see |@https://example.com:docs");
+        assert_eq!(describe("[**bold** link]( url )"), "b@url:bold|@url: link");
+        assert_eq!(describe("[no url]()"), ":[no url]()");
+    }
+
+    #[test]
+    fn parses_code_spans() {
+        assert_eq!(describe("`a *b* c`"), "c:a *b* c");
+        assert_eq!(describe("``a`b``"), "c:a`b");
+        assert_eq!(describe("`` `tick` ``"), "c:`tick`");
+        assert_eq!(describe("``a`"), ":``a`");
+        //代码里的标记不会结束外面的格式
+        assert_eq!(describe("**a `b**` c**"), "b:a |bc:b**|b: c");
+        assert_eq!(describe("**a ``b**`` c**"), "b:a |bc:b**|b: c");
+        assert_eq!(describe("**`b**`**"), "bc:b**");
+    }
+
+    #[test]
+    fn wraps_spans_at_whitespace() {
+        let spans = parse_inline("one **two** three");
+        let lines: Vec<String> = wrap_spans(&spans, 8.0, |_| 1.0).iter()
+            .map(|line| line.iter().map(|span| span.text.as_str()).collect())
+            .collect();
+        assert_eq!(lines, ["one two", "three"]);
+        assert!(wrap_spans(&spans, 8.0, |_| 1.0)[0][1].bold);
+        //比一行还长的单词从中间断开
+        let long = parse_inline("abcdefghij");
+        let lines: Vec<usize> = wrap_spans(&long, 4.0, |_| 1.0).iter().map(|line| line[0].text.len()).collect();
+        assert_eq!(lines, [4, 4, 2]);
+    }
+}
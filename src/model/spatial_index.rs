@@ -0,0 +1,155 @@
+//! 节点和连接曲线在世界坐标下的 R 树索引，用来只绘制、只检测屏幕内的元素
+
+use rstar::primitives::{GeomWithData, Rectangle};
+use rstar::{RTree, AABB};
+use slotmap::SecondaryMap;
+use super::geometry::{vector, Point, Rect};
+use super::node::{Node, BUTTON_SIZE};
+use super::node_graph::{curve_control_points, NodeGraph, NodeId};
+
+/// 索引里的元素：节点本身（含展开按钮），或者节点到父节点的连接曲线
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpatialItem {
+    Node(NodeId),
+    Curve(NodeId),
+}
+
+type Entry = GeomWithData<Rectangle<[f32; 2]>, SpatialItem>;
+
+#[derive(Default)]
+pub struct SpatialIndex {
+    tree: RTree<Entry>,
+    //上一次写入索引的包围盒，用来判断节点是否移动过
+    node_rects: SecondaryMap<NodeId, Rect>,
+    curve_rects: SecondaryMap<NodeId, Rect>,
+    //索引每变化一次加一
+    revision: u64,
+}
+
+impl SpatialIndex {
+    pub fn from_graph(node_graph: &NodeGraph) -> Self {
+        let mut index = Self::default();
+        index.sync(node_graph);
+        index
+    }
+
+    /// 和文档对齐：只更新移动过、新增或删除了的元素，没有变化时不会修改 R 树
+    pub fn sync(&mut self, node_graph: &NodeGraph) {
+        let removed: Vec<NodeId> = self.node_rects.keys().filter(|id| !node_graph.nodes.contains_key(*id)).collect();
+        for id in removed {
+            if let Some(rect) = self.node_rects.remove(id) {
+                self.tree.remove(&entry(rect, SpatialItem::Node(id)));
+                self.revision += 1;
+            }
+        }
+        let removed: Vec<NodeId> = self.curve_rects.keys()
+            .filter(|id| node_graph.nodes.get(*id).and_then(|node| node.father_id).is_none())
+            .collect();
+        for id in removed {
+            if let Some(rect) = self.curve_rects.remove(id) {
+                self.tree.remove(&entry(rect, SpatialItem::Curve(id)));
+                self.revision += 1;
+            }
+        }
+
+        for (id, node) in &node_graph.nodes {
+            let rect = node_bounds(node);
+            self.revision += u64::from(Self::update(&mut self.tree, &mut self.node_rects, id, rect, SpatialItem::Node(id)));
+            if let Some(father) = node.father_id.and_then(|father_id| node_graph.nodes.get(father_id)) {
+                let rect = curve_bounds(&curve_control_points(node, father));
+                self.revision += u64::from(Self::update(&mut self.tree, &mut self.curve_rects, id, rect, SpatialItem::Curve(id)));
+            }
+        }
+    }
+
+    //写入元素的包围盒，有变化时返回 true
+    fn update(tree: &mut RTree<Entry>, rects: &mut SecondaryMap<NodeId, Rect>, id: NodeId, rect: Rect, item: SpatialItem) -> bool {
+        match rects.get(id) {
+            Some(old) if *old == rect => return false,
+            Some(old) => {
+                tree.remove(&entry(*old, item));
+            }
+            None => {}
+        }
+        tree.insert(entry(rect, item));
+        rects.insert(id, rect);
+        true
+    }
+
+    /// 索引的版本号，节点新增、删除、移动、改变大小或父节点时增加，可以用来判断依赖位置的缓存是否过期
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    //与给定矩形相交的所有元素
+    pub fn query(&self, rect: Rect) -> impl Iterator<Item = SpatialItem> + '_ {
+        let envelope = AABB::from_corners([rect.min.x, rect.min.y], [rect.max.x, rect.max.y]);
+        self.tree.locate_in_envelope_intersecting(&envelope).map(|e| e.data)
+    }
+
+    //包含给定点的节点，用于命中检测
+    pub fn nodes_at(&self, p: Point) -> impl Iterator<Item = NodeId> + '_ {
+        self.query(Rect::from_min_max(p, p)).filter_map(|item| match item {
+            SpatialItem::Node(id) => Some(id),
+            SpatialItem::Curve(_) => None,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.size()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.size() == 0
+    }
+}
+
+fn entry(rect: Rect, item: SpatialItem) -> Entry {
+    GeomWithData::new(Rectangle::from_corners([rect.min.x, rect.min.y], [rect.max.x, rect.max.y]), item)
+}
+
+//节点矩形加上右侧的展开按钮
+fn node_bounds(node: &Node) -> Rect {
+    let button = Rect::from_center_size(node.button_pos(), vector(BUTTON_SIZE, BUTTON_SIZE));
+    node.rect().union(button)
+}
+
+//贝塞尔曲线一定在控制点的凸包里，用控制点的包围盒就够了
+fn curve_bounds(points: &[Point; 4]) -> Rect {
+    points.iter().fold(Rect::NOTHING, |rect, p| rect.union(Rect::from_min_max(*p, *p)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use super::*;
+
+    #[test]
+    fn query_finds_items_in_view_and_revision_tracks_changes() {
+        let mut graph = NodeGraph::default();
+        let root = graph.add_node_with_position(Point::ZERO);
+        let child = graph.add_node_with_father_node(root);
+        let far = graph.add_node_with_position(Point::new(10_000.0, 10_000.0));
+        let mut index = SpatialIndex::from_graph(&graph);
+        assert_eq!(index.len(), 4); //三个节点和一条曲线
+        let seen: HashSet<SpatialItem> = index.query(Rect::from_min_max(Point::new(-100.0, -100.0), Point::new(400.0, 100.0))).collect();
+        assert_eq!(seen, HashSet::from([SpatialItem::Node(root), SpatialItem::Node(child), SpatialItem::Curve(child)]));
+        assert_eq!(index.nodes_at(Point::new(10_000.0, 10_000.0)).collect::<Vec<_>>(), [far]);
+
+        //没有变化时版本不变
+        let revision = index.revision();
+        index.sync(&graph);
+        assert_eq!(index.revision(), revision);
+        //移动节点后版本增加，旧位置查不到它
+        graph.set_position(far, Point::new(-10_000.0, 0.0));
+        index.sync(&graph);
+        assert!(index.revision() > revision);
+        assert_eq!(index.nodes_at(Point::new(10_000.0, 10_000.0)).count(), 0);
+        //删除节点时它的曲线也从索引里去掉
+        let revision = index.revision();
+        graph.remove_node(child);
+        index.sync(&graph);
+        assert!(index.revision() > revision);
+        assert_eq!(index.len(), 2);
+    }
+}
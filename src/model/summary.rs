@@ -0,0 +1,234 @@
+//! 概要：用大括号把同一个父节点下连续的几个子节点括起来，括号外面接一个概要节点
+//!
+//! 概要节点是一个没有父节点的普通节点，可以编辑文字、设置样式、再添加子节点；
+//! 它的位置由 `layout_summaries` 根据括号的位置计算，不需要手动摆放。
+
+use serde::{Deserialize, Serialize};
+use super::connector::cubic_point;
+use super::geometry::{Point, Rect, Vector};
+use super::node_graph::{NodeGraph, NodeId};
+
+slotmap::new_key_type! {
+    pub struct SummaryId;
+}
+
+/// 括号和被括起来的节点之间的距离
+pub const BRACE_GAP: f32 = 10.0;
+/// 括号的宽度
+pub const BRACE_WIDTH: f32 = 16.0;
+/// 括号尖端到概要节点的距离
+pub const SUMMARY_TOPIC_GAP: f32 = 30.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Summary {
+    /// 括起来的第一个和最后一个兄弟节点，它们的父节点相同，顺序不限
+    pub from: NodeId,
+    pub to: NodeId,
+    /// 概要节点
+    pub topic: NodeId,
+}
+
+impl NodeGraph {
+    //把 from 到 to 之间的兄弟节点括起来，并新建概要节点；两个节点的父节点必须相同
+    pub fn add_summary(&mut self, from: NodeId, to: NodeId) -> Option<SummaryId> {
+        let father_id = self.nodes.get(from)?.father_id?;
+        if self.nodes.get(to)?.father_id != Some(father_id) {
+            return None;
+        }
+        let topic = self.add_node_with_position(Point::ZERO);
+        self.set_text(topic, "Summary");
+        self.add_summary_topic(from, to, topic)
+    }
+    //用已有的节点作为概要节点，导入文件时用；topic 不能有父节点，也不能已经是概要节点
+    pub fn add_summary_topic(&mut self, from: NodeId, to: NodeId, topic: NodeId) -> Option<SummaryId> {
+        let father_id = self.nodes.get(from)?.father_id?;
+        if self.nodes.get(to)?.father_id != Some(father_id)
+            || self.nodes.get(topic)?.father_id.is_some()
+            || self.topics.contains_key(topic)
+        {
+            return None;
+        }
+        let summary_id = self.summaries.insert(Summary { from, to, topic });
+        self.topics.insert(topic, summary_id);
+        self.touch();
+        self.layout_summaries();
+        Some(summary_id)
+    }
+    //概要括起来的兄弟节点，按子节点顺序
+    pub fn summary_range(&self, summary_id: SummaryId) -> Vec<NodeId> {
+        let Some(summary) = self.summaries.get(summary_id) else { return Vec::new() };
+        let Some(father_id) = self.nodes.get(summary.from).and_then(|node| node.father_id) else { return Vec::new() };
+        let siblings = self.children(father_id);
+        let position = |id| siblings.iter().position(|sibling| *sibling == id);
+        match (position(summary.from), position(summary.to)) {
+            (Some(a), Some(b)) => siblings[a.min(b)..=a.max(b)].to_vec(),
+            _ => Vec::new(),
+        }
+    }
+    //node_id 是概要节点时返回它所属的概要，直接查索引
+    pub fn summary_of_topic(&self, node_id: NodeId) -> Option<SummaryId> {
+        self.topics.get(node_id).copied()
+    }
+    //根据 summaries 重新建立概要节点的索引
+    pub fn rebuild_topic_index(&mut self) {
+        self.topics.clear();
+        for (id, summary) in &self.summaries {
+            self.topics.insert(summary.topic, id);
+        }
+        self.touch();
+    }
+    //括号的位置：返回括号竖直方向的范围、括号贴着的 x 坐标，以及括号朝哪边（1 向右，-1 向左）
+    //子节点在父节点右边时括号画在右边，反之画在左边；visible 过滤掉折叠起来的子孙节点
+    pub fn summary_brace(&self, summary_id: SummaryId, visible: &impl Fn(NodeId) -> bool) -> Option<(Rect, f32)> {
+        let range = self.summary_range(summary_id);
+        let father = self.nodes.get(self.nodes.get(*range.first()?)?.father_id?)?;
+        let mut subtree = Vec::new();
+        for &id in &range {
+            subtree.push(id);
+            subtree.extend(self.query_all_children_nodes(id));
+        }
+        subtree.retain(|id| visible(*id));
+        let bounds = self.bounds_of(subtree)?;
+        let side = if bounds.center().x >= father.node_pos.x { 1.0 } else { -1.0 };
+        Some((bounds, side))
+    }
+    //把每个概要节点放到括号尖端外面，竖直方向对齐括号中间
+    pub fn layout_summaries(&mut self) {
+        let ids: Vec<SummaryId> = self.summaries.keys().collect();
+        for id in ids {
+            let Some((bounds, side)) = self.summary_brace(id, &|_| true) else { continue };
+            let topic = self.summaries[id].topic;
+            let Some(half_width) = self.nodes.get(topic).map(|node| node.node_size.x / 2.0) else { continue };
+            let edge = if side > 0.0 { bounds.max.x } else { bounds.min.x };
+            let x = edge + side * (BRACE_GAP + BRACE_WIDTH + SUMMARY_TOPIC_GAP + half_width);
+            let pos = Point::new(x, bounds.center().y);
+            if self.nodes[topic].node_pos != pos {
+                self.set_position(topic, pos);
+            }
+        }
+    }
+    //node_id 离开原来的父节点前调用：括着它的概要缩小到范围里剩下的兄弟节点，范围里只有它时去掉概要，
+    //返回要一起删除的概要节点；概要节点自己被挂到别的节点下面时变成普通节点，它的概要去掉
+    pub(crate) fn detach_from_summaries(&mut self, node_id: NodeId) -> Vec<NodeId> {
+        if let Some(id) = self.summary_of_topic(node_id) {
+            self.summaries.remove(id);
+            self.topics.remove(node_id);
+            self.touch();
+        }
+        let ids: Vec<SummaryId> = self.summaries.keys().collect();
+        let mut topics = Vec::new();
+        for id in ids {
+            let range = self.summary_range(id);
+            if !range.contains(&node_id) {
+                continue;
+            }
+            let rest: Vec<NodeId> = range.into_iter().filter(|id| *id != node_id).collect();
+            match (rest.first(), rest.last()) {
+                (Some(&from), Some(&to)) => {
+                    let summary = &mut self.summaries[id];
+                    (summary.from, summary.to) = (from, to);
+                }
+                _ => {
+                    let summary = self.summaries.remove(id).expect("id comes from summaries");
+                    self.topics.remove(summary.topic);
+                    topics.push(summary.topic);
+                }
+            }
+            self.touch();
+        }
+        topics
+    }
+    //去掉括起来的节点或概要节点已经不存在的概要，返回要一起删除的概要节点
+    pub(crate) fn remove_dangling_summaries(&mut self) -> Vec<NodeId> {
+        let nodes = &self.nodes;
+        let dangling: Vec<SummaryId> = self.summaries.iter()
+            .filter(|(_, s)| !(nodes.contains_key(s.from) && nodes.contains_key(s.to) && nodes.contains_key(s.topic)))
+            .map(|(id, _)| id)
+            .collect();
+        let mut topics = Vec::new();
+        for id in dangling {
+            let Some(summary) = self.summaries.remove(id) else { continue };
+            self.topics.remove(summary.topic);
+            self.touch();
+            if self.nodes.contains_key(summary.topic) {
+                topics.push(summary.topic);
+            }
+        }
+        topics
+    }
+}
+
+//括号的形状，从上到下的折线，bounds 和 side 来自 `summary_brace`
+pub fn brace_points(bounds: Rect, side: f32) -> Vec<Point> {
+    let x = if side > 0.0 { bounds.max.x + BRACE_GAP } else { bounds.min.x - BRACE_GAP };
+    let (top, bottom) = (Point::new(x, bounds.min.y), Point::new(x, bounds.max.y));
+    let middle = top + (bottom - top) * 0.5;
+    let half = Vector::new(BRACE_WIDTH * side / 2.0, 0.0);
+    let tip = brace_tip(bounds, side);
+    //上下两半各由两段贝塞尔曲线组成，中间的尖端朝外
+    let quarter = (middle - top) * 0.5;
+    let (upper, lower) = (top + half + quarter, middle + half + quarter);
+    let mut points = vec![top];
+    for control in [
+        [top, top + half, upper - quarter * 0.4, upper],
+        [upper, upper + quarter * 0.4, middle + half, tip],
+        [tip, middle + half, lower - quarter * 0.4, lower],
+        [lower, lower + quarter * 0.4, bottom + half, bottom],
+    ] {
+        points.extend((1..=BRACE_SEGMENTS).map(|i| cubic_point(control, i as f32 / BRACE_SEGMENTS as f32)));
+    }
+    points
+}
+
+//括号尖端，概要节点的连线从这里出发
+pub fn brace_tip(bounds: Rect, side: f32) -> Point {
+    let x = if side > 0.0 { bounds.max.x + BRACE_GAP } else { bounds.min.x - BRACE_GAP };
+    Point::new(x + BRACE_WIDTH * side, bounds.center().y)
+}
+
+//括号每一段曲线用多少段折线近似
+const BRACE_SEGMENTS: usize = 8;
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{NodeGraph, Point};
+
+    #[test]
+    fn topic_index_follows_summaries() {
+        let mut graph = NodeGraph::default();
+        let root = graph.add_node_with_position(Point::ZERO);
+        let a = graph.add_node_with_father_node(root);
+        let b = graph.add_node_with_father_node(root);
+        let id = graph.add_summary(a, b).unwrap();
+        let topic = graph.summaries[id].topic;
+        assert_eq!(graph.summary_of_topic(topic), Some(id));
+        assert_eq!(graph.summary_of_topic(a), None);
+        //读取文件后重建索引
+        let loaded: NodeGraph = serde_json::from_str(&serde_json::to_string(&graph).unwrap()).unwrap();
+        assert_eq!(loaded.summary_of_topic(topic), Some(id));
+        //删除括起来的节点时概要和概要节点一起删除
+        assert!(graph.remove_node(a).contains(&topic));
+        assert_eq!(graph.summary_of_topic(topic), None);
+        assert!(graph.summaries.is_empty());
+    }
+
+    #[test]
+    fn moving_summarized_nodes_keeps_ranges_valid() {
+        let mut graph = NodeGraph::default();
+        let root = graph.add_node_with_position(Point::ZERO);
+        let other = graph.add_node_with_position(Point::ZERO);
+        let [a, b, c] = [(); 3].map(|_| graph.add_node_with_father_node(root));
+        let id = graph.add_summary(a, c).unwrap();
+        let single = graph.add_summary(b, b).unwrap();
+        let single_topic = graph.summaries[single].topic;
+        //移走一端时范围缩小到剩下的节点
+        graph.set_father(c, Some(other)).unwrap();
+        assert_eq!(graph.summary_range(id), vec![a, b]);
+        //移走唯一括着的节点时概要和概要节点一起去掉
+        graph.set_father(b, Some(other)).unwrap();
+        assert_eq!(graph.summary_range(id), vec![a]);
+        assert!(!graph.summaries.contains_key(single));
+        assert!(!graph.nodes.contains_key(single_topic));
+        assert!(graph.validate().is_empty());
+    }
+}
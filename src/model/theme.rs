@@ -0,0 +1,197 @@
+//! 导图主题：背景、各层级节点的默认样式、连线、高亮和编辑框的颜色
+//!
+//! 主题随文档一起保存。节点自己没设置的样式属性从主题里对应层级的样式取，所以切换主题会改变这些节点的外观。
+
+use serde::{Deserialize, Serialize};
+use super::connector::ConnectorStyle;
+use super::geometry::Color;
+use super::node::{ResolvedStyle, DEFAULT_FONT_SIZE, DEFAULT_NODE_COLOR};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub name: String,
+    /// 深色主题，界面的其它部分也应该用深色
+    pub dark: bool,
+    pub background: Color,
+    pub grid: Color,
+    /// 各层级节点的默认样式，第一个是根节点，比最后一个更深的层级都用最后一个
+    pub depth_styles: Vec<ResolvedStyle>,
+    pub connector: ConnectorStyle,
+    /// 选中和悬停时节点外面的高亮圈
+    pub selection: Color,
+    pub hover: Color,
+    /// 编辑文字时编辑框的颜色
+    pub edit_background: Color,
+    pub edit_text: Color,
+    /// 自动分支颜色：根节点的每个子节点按顺序从 branch_palette 取一个颜色，子孙节点和连线沿用
+    pub branch_colors: bool,
+    pub branch_palette: Vec<Color>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+impl Theme {
+    pub fn light() -> Self {
+        let base = ResolvedStyle::default();
+        Self {
+            name: "Light".to_owned(),
+            dark: false,
+            background: Color::WHITE,
+            grid: Color::from_gray(220),
+            depth_styles: vec![
+                ResolvedStyle { fill: Color::from_rgb(110, 110, 230), text_color: Color::WHITE, font_size: 18.0, bold: true, ..base },
+                ResolvedStyle { fill: DEFAULT_NODE_COLOR, font_size: 15.0, ..base },
+                ResolvedStyle { fill: Color::from_rgb(205, 205, 255), ..base },
+            ],
+            connector: ConnectorStyle::default(),
+            selection: Color::from_rgb(250, 0, 0),
+            hover: Color::from_rgb(200, 150, 250),
+            edit_background: Color::from_rgb(123, 123, 0),
+            edit_text: Color::from_rgb(0, 255, 0),
+            branch_colors: false,
+            branch_palette: vec![
+                Color::from_rgb(231, 76, 60),
+                Color::from_rgb(52, 152, 219),
+                Color::from_rgb(46, 204, 113),
+                Color::from_rgb(243, 156, 18),
+                Color::from_rgb(155, 89, 182),
+                Color::from_rgb(26, 188, 156),
+                Color::from_rgb(236, 112, 160),
+            ],
+        }
+    }
+
+    pub fn dark() -> Self {
+        let base = ResolvedStyle { text_color: Color::from_gray(225), ..ResolvedStyle::default() };
+        Self {
+            name: "Dark".to_owned(),
+            dark: true,
+            background: Color::from_rgb(30, 30, 36),
+            grid: Color::from_rgb(50, 50, 60),
+            depth_styles: vec![
+                ResolvedStyle { fill: Color::from_rgb(90, 110, 200), text_color: Color::WHITE, font_size: 18.0, bold: true, ..base },
+                ResolvedStyle { fill: Color::from_rgb(60, 70, 120), font_size: 15.0, ..base },
+                ResolvedStyle { fill: Color::from_rgb(45, 50, 80), ..base },
+            ],
+            connector: ConnectorStyle { color: Color::from_rgb(120, 160, 255), width: 2.0, ..ConnectorStyle::default() },
+            selection: Color::from_rgb(255, 200, 0),
+            hover: Color::from_rgb(150, 150, 255),
+            edit_background: Color::from_rgb(20, 20, 30),
+            edit_text: Color::WHITE,
+            branch_colors: false,
+            branch_palette: vec![
+                Color::from_rgb(192, 57, 43),
+                Color::from_rgb(41, 128, 185),
+                Color::from_rgb(39, 174, 96),
+                Color::from_rgb(211, 128, 0),
+                Color::from_rgb(142, 68, 173),
+                Color::from_rgb(22, 160, 133),
+                Color::from_rgb(200, 80, 130),
+            ],
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        let base = ResolvedStyle {
+            fill: Color::BLACK,
+            border_color: Color::WHITE,
+            border_width: 2.0,
+            text_color: Color::WHITE,
+            font_size: DEFAULT_FONT_SIZE + 2.0,
+            bold: true,
+            italic: false,
+        };
+        Self {
+            name: "High contrast".to_owned(),
+            dark: true,
+            background: Color::BLACK,
+            grid: Color::from_gray(60),
+            depth_styles: vec![
+                ResolvedStyle { border_color: Color::from_rgb(255, 255, 0), border_width: 3.0, font_size: 22.0, ..base },
+                base,
+            ],
+            connector: ConnectorStyle { color: Color::from_rgb(255, 255, 0), width: 3.0, ..ConnectorStyle::default() },
+            selection: Color::from_rgb(0, 255, 255),
+            hover: Color::from_rgb(255, 0, 255),
+            edit_background: Color::BLACK,
+            edit_text: Color::from_rgb(255, 255, 0),
+            branch_colors: false,
+            branch_palette: vec![
+                Color::from_rgb(255, 255, 0),
+                Color::from_rgb(0, 255, 255),
+                Color::from_rgb(255, 0, 255),
+                Color::from_rgb(0, 255, 0),
+                Color::WHITE,
+            ],
+        }
+    }
+
+    //内置主题，用于主题菜单
+    pub fn builtin() -> Vec<Theme> {
+        vec![Self::light(), Self::dark(), Self::high_contrast()]
+    }
+
+    //分支颜色 color 下第 depth 层节点的默认样式：一级分支直接用分支颜色填充，更深的层级和背景混合变浅
+    //文字颜色按填充色的亮度选黑或白
+    pub fn branch_style(&self, depth: usize, color: Color) -> ResolvedStyle {
+        let mut style = self.node_style(depth);
+        style.fill = if depth <= 2 { color } else { color.lerp(self.background, 0.5) };
+        if style.border_width > 0.0 {
+            style.border_color = color;
+        }
+        style.text_color = if style.fill.luminance() > 0.55 { Color::BLACK } else { Color::WHITE };
+        style
+    }
+
+    //第 depth 层（根节点为 1）节点的默认样式
+    pub fn node_style(&self, depth: usize) -> ResolvedStyle {
+        let index = depth.saturating_sub(1).min(self.depth_styles.len().saturating_sub(1));
+        self.depth_styles.get(index).copied().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deeper_levels_reuse_the_last_depth_style() {
+        let theme = Theme::light();
+        assert_eq!(theme.node_style(0), theme.depth_styles[0]);
+        assert_eq!(theme.node_style(1), theme.depth_styles[0]);
+        assert_eq!(theme.node_style(3), theme.depth_styles[2]);
+        assert_eq!(theme.node_style(10), theme.depth_styles[2]);
+        let empty = Theme { depth_styles: Vec::new(), ..Theme::light() };
+        assert_eq!(empty.node_style(2), ResolvedStyle::default());
+    }
+
+    #[test]
+    fn themes_survive_a_save_and_fill_in_missing_fields() {
+        for theme in Theme::builtin() {
+            let json = serde_json::to_string(&theme).unwrap();
+            assert_eq!(serde_json::from_str::<Theme>(&json).unwrap(), theme);
+        }
+        //旧版本保存的主题缺少后来加的字段
+        let theme: Theme = serde_json::from_str(r#"{"name":"Old","dark":true}"#).unwrap();
+        assert_eq!(theme, Theme { name: "Old".to_owned(), dark: true, ..Theme::light() });
+    }
+
+    #[test]
+    fn branch_styles_fill_with_the_branch_color() {
+        let theme = Theme::light();
+        let dark_red = Color::from_rgb(120, 0, 0);
+        let first = theme.branch_style(2, dark_red);
+        assert_eq!((first.fill, first.text_color, first.font_size), (dark_red, Color::WHITE, theme.node_style(2).font_size));
+        //更深的层级和背景混合变浅，文字跟着换成黑色
+        let deeper = theme.branch_style(3, Color::from_rgb(60, 60, 60));
+        assert_eq!((deeper.fill, deeper.text_color), (Color::from_rgb(158, 158, 158), Color::BLACK));
+        //没有边框的主题不画边框，有边框的主题边框也用分支颜色
+        assert_eq!(first.border_width, 0.0);
+        assert_eq!(Theme::high_contrast().branch_style(4, dark_red).border_color, dark_red);
+    }
+}
@@ -0,0 +1,169 @@
+//! 从根节点往下遍历一次得到的树信息，按导图的修改版本缓存
+//!
+//! 逐个节点沿父节点往上找的代价和深度成正比，整个导图加起来是节点数乘深度；
+//! 这里一次遍历算出所有节点的结果，导图或者主题里的分支颜色、连线类型修改后下次用到时再重新计算。
+
+use std::rc::Rc;
+use slotmap::SecondaryMap;
+use super::connector::ConnectorKind;
+use super::geometry::Color;
+use super::node_graph::{NodeGraph, NodeId};
+
+/// 一次遍历算出的树信息；父节点链上有环或者父节点不存在的节点遍历不到，不在里面
+#[derive(Debug, Default)]
+pub struct TreeInfo {
+    //计算时导图的版本和用到的主题设置
+    revision: u64,
+    theme: ThemeKey,
+    /// 节点的层级，根节点和概要节点为 1
+    pub depth: SecondaryMap<NodeId, usize>,
+    /// 节点所在分支的颜色，没有分支颜色的节点不在里面
+    pub branch_color: SecondaryMap<NodeId, Color>,
+    /// 连到父节点的连线类型：自己或最近的祖先设置的，都没有时用主题的
+    pub connector_kind: SecondaryMap<NodeId, ConnectorKind>,
+}
+
+//主题里影响树信息的设置，main.rs 会直接修改主题，所以和版本一起比较
+#[derive(Debug, Default, PartialEq)]
+struct ThemeKey {
+    branch_colors: bool,
+    branch_palette: Vec<Color>,
+    connector_kind: ConnectorKind,
+}
+
+impl NodeGraph {
+    //当前版本的树信息，导图或主题修改过时重新计算
+    pub fn tree_info(&self) -> Rc<TreeInfo> {
+        let mut cache = self.tree_cache.borrow_mut();
+        match cache.as_ref() {
+            Some(info) if info.revision == self.revision() && info.theme == self.theme_key() => info.clone(),
+            _ => {
+                let info = Rc::new(self.compute_tree_info());
+                *cache = Some(info.clone());
+                info
+            }
+        }
+    }
+    fn theme_key(&self) -> ThemeKey {
+        ThemeKey {
+            branch_colors: self.theme.branch_colors,
+            branch_palette: self.theme.branch_palette.clone(),
+            connector_kind: self.theme.connector.kind,
+        }
+    }
+    //从所有根节点开始按子节点索引往下走，每个节点只访问一次；
+    //概要节点不是一级分支，它沿用括起来的第一个节点的分支颜色和连线类型，所以等那个节点算完再从它开始走
+    fn compute_tree_info(&self) -> TreeInfo {
+        let theme = self.theme_key();
+        let palette = if theme.branch_colors { theme.branch_palette.as_slice() } else { &[] };
+        let mut info = TreeInfo { revision: self.revision(), theme: ThemeKey::default(), ..TreeInfo::default() };
+        //栈里是节点、层级和从父节点继承的分支颜色、连线类型
+        let mut stack: Vec<(NodeId, usize, Option<Color>, ConnectorKind)> = self.nodes.iter()
+            .filter(|(id, node)| node.father_id.is_none() && self.summary_of_topic(*id).is_none())
+            .map(|(id, _)| (id, 1, None, theme.connector_kind))
+            .collect();
+        let mut topics: Vec<(NodeId, NodeId)> = self.summaries.values().map(|summary| (summary.topic, summary.from)).collect();
+        loop {
+            while let Some((id, depth, inherited, kind)) = stack.pop() {
+                if info.depth.insert(id, depth).is_some() {
+                    continue; //子节点索引没有重建时可能重复，validate 会报告
+                }
+                let style = &self.nodes[id].node_style;
+                let color = style.branch_color.or(inherited);
+                let kind = style.connector.unwrap_or(kind);
+                if let Some(color) = color {
+                    info.branch_color.insert(id, color);
+                }
+                info.connector_kind.insert(id, kind);
+                //没有颜色的根节点下面，每个一级分支按顺序从调色板取颜色
+                let first_level = depth == 1 && color.is_none() && self.summary_of_topic(id).is_none() && !palette.is_empty();
+                stack.extend(self.children(id).iter().enumerate().map(|(i, &child)| {
+                    let color = if first_level { Some(palette[i % palette.len()]) } else { color };
+                    (child, depth + 1, color, kind)
+                }));
+            }
+            //括起来的节点已经算完的概要节点；概要可能括着别的概要节点的子节点，所以一轮一轮地找
+            let ready = topics.iter().position(|(_, from)| info.depth.contains_key(*from))
+                .or_else(|| (!topics.is_empty()).then_some(0));
+            let Some(index) = ready else { break };
+            let (topic, from) = topics.swap_remove(index);
+            if self.nodes.contains_key(topic) {
+                let color = info.branch_color.get(from).copied();
+                let kind = info.connector_kind.get(from).copied().unwrap_or(theme.connector_kind);
+                stack.push((topic, 1, color, kind));
+            }
+        }
+        info.theme = theme;
+        info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{Color, ConnectorKind, NodeGraph, NodeStyle, Point};
+
+    #[test]
+    fn depths_follow_changes_and_skip_cycles() {
+        let mut graph = NodeGraph::default();
+        let root = graph.add_node_with_position(Point::ZERO);
+        let a = graph.add_node_with_father_node(root);
+        let b = graph.add_node_with_father_node(a);
+        assert_eq!([root, a, b].map(|id| graph.node_depth(id)), [1, 2, 3]);
+        graph.set_father(b, Some(root)).unwrap();
+        assert_eq!(graph.node_depth(b), 2);
+        //直接改出一个环：a 和 b 互为父节点，从根节点走不到它们
+        graph.nodes[a].father_id = Some(b);
+        graph.nodes[b].father_id = Some(a);
+        graph.rebuild_children_index();
+        let tree = graph.tree_info();
+        assert_eq!(tree.depth.get(root), Some(&1));
+        assert!(!tree.depth.contains_key(a) && !tree.depth.contains_key(b));
+        assert!(graph.node_depth(a) > graph.nodes.len());
+        assert_eq!(graph.validate().len(), 2);
+    }
+
+    #[test]
+    fn summary_topics_follow_their_branch() {
+        let mut graph = NodeGraph::default();
+        graph.theme.branch_colors = true;
+        let palette = graph.theme.branch_palette.clone();
+        let root = graph.add_node_with_position(Point::ZERO);
+        let a = graph.add_node_with_father_node(root);
+        let b = graph.add_node_with_father_node(root);
+        let summary = graph.add_summary(a, a).unwrap();
+        let topic = graph.summaries[summary].topic;
+        let topic_child = graph.add_node_with_father_node(topic);
+        graph.set_style(a, NodeStyle { connector: Some(ConnectorKind::Elbow), ..NodeStyle::default() });
+        //概要节点不算一级分支，它和它的子节点沿用括起来的节点的颜色和连线类型
+        assert_eq!([a, b, topic, topic_child].map(|id| graph.branch_color(id)), [Some(palette[0]), Some(palette[1]), Some(palette[0]), Some(palette[0])]);
+        assert_eq!(graph.connector_style(topic_child).kind, ConnectorKind::Elbow);
+        assert_eq!(graph.connector_style(b).kind, graph.theme.connector.kind);
+        //直接修改主题后缓存也会失效
+        graph.theme.branch_colors = false;
+        assert_eq!(graph.branch_color(topic_child), None);
+        let red = Color::from_rgb(255, 0, 0);
+        graph.set_style(topic, NodeStyle { branch_color: Some(red), ..NodeStyle::default() });
+        assert_eq!(graph.branch_color(topic_child), Some(red));
+    }
+
+    #[test]
+    fn branch_colors_cycle_and_overrides_cover_the_subtree() {
+        let mut graph = NodeGraph::default();
+        graph.theme.branch_colors = true;
+        let palette = graph.theme.branch_palette.clone();
+        let root = graph.add_node_with_position(Point::ZERO);
+        let branches: Vec<_> = (0..=palette.len()).map(|_| graph.add_node_with_father_node(root)).collect();
+        let leaf = graph.add_node_with_father_node(branches[1]);
+        assert_eq!(graph.branch_color(root), None);
+        //调色板用完后从头开始
+        assert_eq!(graph.branch_color(branches[palette.len()]), Some(palette[0]));
+        assert_eq!(graph.branch_color(leaf), Some(palette[1]));
+        assert_eq!(graph.node_style(leaf).fill, graph.theme.branch_style(3, palette[1]).fill);
+        //自己设置的分支颜色覆盖子孙节点，自己设置的填充色优先于分支颜色
+        let red = Color::from_rgb(255, 0, 0);
+        graph.set_style(branches[1], NodeStyle { branch_color: Some(red), ..NodeStyle::default() });
+        assert_eq!(graph.connector_color(leaf), red);
+        graph.set_style(leaf, NodeStyle { fill: Some(Color::WHITE), ..NodeStyle::default() });
+        assert_eq!(graph.node_style(leaf).fill, Color::WHITE);
+    }
+}
@@ -0,0 +1,115 @@
+//! 边界的绘制和选择：画在节点下面，点击边界里的空白处选中它，Delete 去掉选中的边界
+
+use egui::{Align2, Color32, ComboBox, FontId, Key, Pos2, Response, Shape, Stroke, Ui};
+use crate::model::boundary::{polygon_bounds, polygon_contains};
+use crate::model::{BoundaryShape, NodeGraph, NodeId, Point, Rect};
+use super::node_graph::{is_node_visible, CacheKey, GraphState};
+use super::pan_zoom::PanZoom;
+use super::settings::ViewSettings;
+
+//边界标题 100% 缩放时的字号
+const TITLE_FONT_SIZE: f32 = 13.0;
+
+/// 边界外框的缓存，节点移动、改变大小、折叠或者边界的形状改变后才重新计算
+#[derive(Default)]
+pub struct BoundaryCache {
+    key: Option<CacheKey>,
+    //没有折叠起来的边界、它们的外框和外框的包围盒（世界坐标），外层的边界在前面
+    outlines: Vec<(NodeId, Vec<Point>, Rect)>,
+}
+
+//需要时重新计算外框，返回缓存里的外框
+fn visible_boundaries<'a>(node_graph: &NodeGraph, graph_state: &'a mut GraphState) -> &'a [(NodeId, Vec<Point>, Rect)] {
+    let key = Some(graph_state.cache_key(node_graph));
+    if key != graph_state.boundary_cache.key {
+        let outlines = node_graph.boundary_outlines(&|id| is_node_visible(node_graph, id, graph_state)).into_iter()
+            .map(|(id, outline)| {
+                let bounds = polygon_bounds(&outline);
+                (id, outline, bounds)
+            })
+            .collect();
+        graph_state.boundary_cache = BoundaryCache { key, outlines };
+    }
+    &graph_state.boundary_cache.outlines
+}
+
+//在节点之前调用，边界画在节点下面
+pub fn draw_boundaries(ui: &Ui, node_graph: &NodeGraph, graph_state: &mut GraphState, pan_zoom: &PanZoom, settings: &ViewSettings) {
+    let transform = pan_zoom.transform;
+    let selected = graph_state.selected_boundary;
+    for &(id, ref outline, bounds) in visible_boundaries(node_graph, graph_state) {
+        let screen_bounds = transform * egui::Rect::from(bounds);
+        if !ui.clip_rect().intersects(screen_bounds) {
+            continue;
+        }
+        let Some(boundary) = node_graph.nodes[id].boundary.as_ref() else { continue };
+        let color = Color32::from(boundary.color);
+        let fill = boundary.fill.map_or(Color32::TRANSPARENT, Color32::from);
+        let width = if selected == Some(id) { 3.0 } else { 1.5 };
+        let points: Vec<Pos2> = outline.iter().map(|p| transform * Pos2::from(*p)).collect();
+        ui.painter().add(Shape::convex_polygon(points, fill, Stroke::new(width, color)));
+        if !boundary.title.is_empty() && settings.lod(transform.scaling).text > 0.0 {
+            let font = FontId::proportional(TITLE_FONT_SIZE * transform.scaling);
+            ui.painter().text(screen_bounds.left_top(), Align2::LEFT_BOTTOM, &boundary.title, font, color);
+        }
+    }
+}
+
+//点击画布空白处时选中包含点击位置的最内层边界，点到关系连线时不选边界
+pub fn select_boundary(ui: &Ui, node_graph: &mut NodeGraph, graph_state: &mut GraphState, pan_zoom: &PanZoom, canvas_response: &Response) {
+    if let Some(pos) = canvas_response.clicked().then(|| canvas_response.interact_pointer_pos()).flatten() {
+        let world = Point::from(pan_zoom.transform.inverse() * pos);
+        graph_state.selected_boundary = if graph_state.selected_link.is_some() {
+            None
+        } else {
+            visible_boundaries(node_graph, graph_state).iter()
+                .rev()
+                .find(|(_, outline, bounds)| bounds.contains(world) && polygon_contains(outline, world))
+                .map(|(id, _, _)| *id)
+        };
+    }
+    let Some(selected) = graph_state.selected_boundary else { return };
+    if !ui.ctx().wants_keyboard_input() && ui.input(|i| i.key_pressed(Key::Delete)) {
+        node_graph.set_boundary(selected, None);
+        graph_state.selected_boundary = None;
+    }
+}
+
+/// 编辑一个节点的边界，返回是否有修改
+pub fn boundary_panel(ui: &mut Ui, node_graph: &mut NodeGraph, node_id: NodeId) -> bool {
+    let Some(boundary) = node_graph.nodes.get_mut(node_id).and_then(|node| node.boundary.as_mut()) else {
+        return false;
+    };
+    let mut changed = false;
+    egui::Grid::new("boundary").num_columns(2).show(ui, |ui| {
+        ui.label("Title");
+        changed |= ui.text_edit_singleline(&mut boundary.title).changed();
+        ui.end_row();
+        ui.label("Shape");
+        ComboBox::from_id_salt("boundary_shape").selected_text(boundary.shape.name()).show_ui(ui, |ui| {
+            for shape in BoundaryShape::ALL {
+                changed |= ui.selectable_value(&mut boundary.shape, shape, shape.name()).changed();
+            }
+        });
+        ui.end_row();
+        ui.label("Color");
+        let mut color = Color32::from(boundary.color);
+        if ui.color_edit_button_srgba(&mut color).changed() {
+            boundary.color = color.into();
+            changed = true;
+        }
+        ui.end_row();
+        let mut filled = boundary.fill.is_some();
+        changed |= ui.checkbox(&mut filled, "Fill").changed();
+        let mut fill = Color32::from(boundary.fill.unwrap_or(boundary.color));
+        ui.add_enabled_ui(filled, |ui| {
+            changed |= ui.color_edit_button_srgba(&mut fill).changed();
+        });
+        boundary.fill = filled.then(|| fill.into());
+        ui.end_row();
+    });
+    if changed {
+        node_graph.touch();
+    }
+    changed
+}
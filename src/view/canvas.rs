@@ -0,0 +1,104 @@
+//! 画布背景网格：画在世界坐标里，跟着节点一起平移缩放
+//!
+//! 缩小时网格间距按 `major_every` 倍放大，屏幕上的线不会挤成一片；新出现的细线会渐显。
+
+use eframe::egui;
+use egui::{Color32, Stroke, Ui};
+use crate::model::Point;
+use super::pan_zoom::PanZoom;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Canvas {
+    pub visible: bool,
+    /// 100% 缩放时的网格间距（世界坐标）
+    pub spacing: f32,
+    /// 屏幕上两条细线之间的最小距离，小于它时换成更大的间距
+    pub min_screen_spacing: f32,
+    /// 每隔多少条细线画一条粗线，也是间距放大的倍数
+    pub major_every: u32,
+    /// 拖动节点时让节点中心对齐到网格
+    pub snap: bool,
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            spacing: 20.0,
+            min_screen_spacing: 12.0,
+            major_every: 5,
+            snap: false,
+        }
+    }
+}
+
+impl Canvas {
+    //当前缩放比例下实际使用的网格间距（世界坐标）
+    pub fn spacing_at(&self, scaling: f32) -> f32 {
+        let factor = self.major_every.max(2) as f32;
+        let mut spacing = self.spacing.max(f32::EPSILON);
+        while spacing * scaling < self.min_screen_spacing {
+            spacing *= factor;
+        }
+        spacing
+    }
+
+    //把世界坐标下的点对齐到当前可见的网格上
+    pub fn snap(&self, p: Point, scaling: f32) -> Point {
+        let spacing = self.spacing_at(scaling);
+        Point::new((p.x / spacing).round() * spacing, (p.y / spacing).round() * spacing)
+    }
+
+    //在 ui 的 clip rect 里画网格，要在节点之前调用，颜色由主题决定
+    pub fn draw(&self, ui: &Ui, pan_zoom: &PanZoom, color: Color32) {
+        let transform = pan_zoom.transform;
+        let clip = ui.clip_rect();
+        let view = transform.inverse() * clip;
+        let spacing = self.spacing_at(transform.scaling);
+        let major_every = self.major_every.max(1) as i64;
+
+        //细线在刚换到这个间距时是透明的，屏幕间距变成两倍最小间距时完全显示
+        let minor_alpha = (spacing * transform.scaling / self.min_screen_spacing - 1.0).clamp(0.0, 1.0);
+        let major = Stroke::new(1.0, color);
+        let minor = Stroke::new(1.0, color.gamma_multiply(0.5 * minor_alpha));
+        let painter = ui.painter();
+
+        let first = (view.min.x / spacing).floor() as i64;
+        let last = (view.max.x / spacing).ceil() as i64;
+        for i in first..=last {
+            let x = transform.translation.x + i as f32 * spacing * transform.scaling;
+            let stroke = if i % major_every == 0 { major } else { minor };
+            painter.vline(x, clip.y_range(), stroke);
+        }
+        let first = (view.min.y / spacing).floor() as i64;
+        let last = (view.max.y / spacing).ceil() as i64;
+        for i in first..=last {
+            let y = transform.translation.y + i as f32 * spacing * transform.scaling;
+            let stroke = if i % major_every == 0 { major } else { minor };
+            painter.hline(clip.x_range(), y, stroke);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spacing_grows_by_major_every_when_zoomed_out() {
+        let canvas = Canvas::default();
+        assert_eq!(canvas.spacing_at(1.0), 20.0);
+        assert_eq!(canvas.spacing_at(0.5), 100.0);
+        assert_eq!(canvas.spacing_at(0.02), 2500.0);
+        for scaling in [0.01, 0.1, 0.3, 1.0, 4.0] {
+            assert!(canvas.spacing_at(scaling) * scaling >= canvas.min_screen_spacing);
+        }
+    }
+
+    #[test]
+    fn snapping_rounds_to_the_visible_grid() {
+        let canvas = Canvas::default();
+        assert_eq!(canvas.snap(Point::new(29.0, -31.0), 1.0), Point::new(20.0, -40.0));
+        assert_eq!(canvas.snap(Point::new(149.0, 151.0), 0.5), Point::new(100.0, 200.0));
+    }
+}
@@ -0,0 +1,53 @@
+//! 父子连线上的文字：双击连线开始编辑，回车或点击别处结束
+
+use egui::{Id, Pos2, TextEdit, Ui, Vec2};
+use crate::model::connector::{connector_midpoint, connector_points};
+use crate::model::{NodeGraph, NodeId, Point};
+use super::cross_link::PICK_DISTANCE;
+use super::node_graph::{connector_screen_points, is_node_visible, near_polyline, GraphState, CURVE_SEGMENTS};
+use super::pan_zoom::PanZoom;
+use super::settings::ViewSettings;
+
+//编辑框的宽度
+const EDIT_WIDTH: f32 = 120.0;
+
+//屏幕上 pos 附近的父子连线，返回连线的子节点
+pub fn connector_at(node_graph: &NodeGraph, graph_state: &GraphState, pan_zoom: &PanZoom, settings: &ViewSettings, pos: Pos2) -> Option<NodeId> {
+    node_graph.nodes.iter()
+        .filter(|(id, node)| node.father_id.is_some() && is_node_visible(node_graph, *id, graph_state))
+        .map(|(id, _)| id)
+        .find(|&id| {
+            connector_screen_points(node_graph, pan_zoom, settings, id).is_some_and(|control| {
+                let kind = node_graph.connector_style(id).kind;
+                near_polyline(&connector_points(kind, control.map(Point::from), CURVE_SEGMENTS), pos, PICK_DISTANCE)
+            })
+        })
+}
+
+//开始编辑 node_id 到父节点连线上的文字
+pub fn start_editing(ui: &Ui, graph_state: &mut GraphState, node_id: NodeId) {
+    graph_state.editing_label = Some(node_id);
+    ui.memory_mut(|memory| memory.request_focus(edit_id(node_id)));
+}
+
+//在连线中间显示编辑框，失去焦点时结束编辑
+pub fn edit_connector_label(ui: &mut Ui, node_graph: &mut NodeGraph, graph_state: &mut GraphState, pan_zoom: &PanZoom, settings: &ViewSettings) {
+    let Some(node_id) = graph_state.editing_label else { return };
+    let Some(control) = connector_screen_points(node_graph, pan_zoom, settings, node_id) else {
+        graph_state.editing_label = None;
+        return;
+    };
+    let kind = node_graph.connector_style(node_id).kind;
+    let center = Pos2::from(connector_midpoint(kind, control.map(Point::from)));
+    let rect = egui::Rect::from_center_size(center, Vec2::new(EDIT_WIDTH, ui.spacing().interact_size.y));
+    let Some(node) = node_graph.nodes.get_mut(node_id) else { return };
+    let response = ui.put(rect, TextEdit::singleline(&mut node.connector_label).id(edit_id(node_id)).hint_text("label"));
+    if response.lost_focus() {
+        node.connector_label = node.connector_label.trim().to_owned();
+        graph_state.editing_label = None;
+    }
+}
+
+fn edit_id(node_id: NodeId) -> Id {
+    Id::new(("connector_label", node_id))
+}
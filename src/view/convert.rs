@@ -0,0 +1,53 @@
+//! 模型层的几何和颜色类型与 egui 类型之间的转换
+
+use egui::{Color32, Pos2, Vec2};
+use crate::model::{Color, Point, Rect, Vector};
+
+impl From<Point> for Pos2 {
+    fn from(p: Point) -> Self {
+        Pos2::new(p.x, p.y)
+    }
+}
+
+impl From<Pos2> for Point {
+    fn from(p: Pos2) -> Self {
+        Point::new(p.x, p.y)
+    }
+}
+
+impl From<Vector> for Vec2 {
+    fn from(v: Vector) -> Self {
+        Vec2::new(v.x, v.y)
+    }
+}
+
+impl From<Vec2> for Vector {
+    fn from(v: Vec2) -> Self {
+        Vector::new(v.x, v.y)
+    }
+}
+
+impl From<Rect> for egui::Rect {
+    fn from(r: Rect) -> Self {
+        egui::Rect::from_min_max(r.min.into(), r.max.into())
+    }
+}
+
+impl From<egui::Rect> for Rect {
+    fn from(r: egui::Rect) -> Self {
+        Rect::from_min_max(r.min.into(), r.max.into())
+    }
+}
+
+impl From<Color> for Color32 {
+    fn from(c: Color) -> Self {
+        Color32::from_rgba_unmultiplied(c.r, c.g, c.b, c.a)
+    }
+}
+
+impl From<Color32> for Color {
+    fn from(c: Color32) -> Self {
+        let [r, g, b, a] = c.to_srgba_unmultiplied();
+        Color::from_rgba(r, g, b, a)
+    }
+}
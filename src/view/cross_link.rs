@@ -0,0 +1,108 @@
+//! 关系连线的绘制和编辑：点击连线选中，选中后两个控制点可以拖动，Delete 删除
+
+use egui::{Color32, ComboBox, Key, Pos2, Response, Sense, Stroke, Ui};
+use crate::model::connector::{connector_midpoint, connector_points};
+use crate::model::{ConnectorKind, LinkId, NodeGraph, Point};
+use super::node_graph::{is_node_visible, near_polyline, paint_connector, paint_label, GraphState, CURVE_SEGMENTS};
+use super::pan_zoom::PanZoom;
+
+//离连线多近（屏幕像素）时点击算选中
+pub(super) const PICK_DISTANCE: f32 = 6.0;
+//控制点拖动手柄的半径
+const HANDLE_RADIUS: f32 = 5.0;
+
+//画所有两端都可见的关系连线，处理选中、拖动控制点和删除
+//canvas_response 是画布空白处的响应，节点上的点击不会传到这里
+pub fn draw_cross_links(ui: &mut Ui, node_graph: &mut NodeGraph, graph_state: &mut GraphState, pan_zoom: &PanZoom, canvas_response: &Response) {
+    let transform = pan_zoom.transform;
+    let mut picked = None;
+    let click_pos = canvas_response.clicked().then(|| canvas_response.interact_pointer_pos()).flatten();
+    let links: Vec<LinkId> = node_graph.links.iter()
+        .filter(|(_, link)| is_node_visible(node_graph, link.source, graph_state) && is_node_visible(node_graph, link.target, graph_state))
+        .map(|(id, _)| id)
+        .collect();
+
+    for &id in &links {
+        let Some(control) = node_graph.link_control_points(id) else { continue };
+        let link = &node_graph.links[id];
+        let screen = control.map(|p| transform * Pos2::from(p));
+        let selected = graph_state.selected_link == Some(id);
+        let mut style = link.style;
+        if selected {
+            style.width += 1.5;
+        }
+        paint_connector(ui, &style, screen, (style.width, style.width * 3.0));
+
+        if !link.label.is_empty() {
+            let center = Pos2::from(connector_midpoint(link.style.kind, screen.map(Point::from)));
+            let background = Color32::from(node_graph.theme.background);
+            paint_label(ui, &link.label, center, Color32::from(link.style.color), background, transform.scaling);
+        }
+
+        if let Some(pos) = click_pos {
+            let points = connector_points(link.style.kind, screen.map(Point::from), CURVE_SEGMENTS);
+            if near_polyline(&points, pos, PICK_DISTANCE) {
+                picked = Some(id);
+            }
+        }
+    }
+    if click_pos.is_some() {
+        graph_state.selected_link = picked;
+    }
+
+    let Some(selected) = graph_state.selected_link.filter(|id| links.contains(id)) else { return };
+    let Some(control) = node_graph.link_control_points(selected) else { return };
+    //控制点和对应的端点之间画一条细线，index 0 靠近 source
+    for (index, (end, handle)) in [(control[3], control[2]), (control[0], control[1])].into_iter().enumerate() {
+        let (end, handle) = (transform * Pos2::from(end), transform * Pos2::from(handle));
+        let color = Color32::from(node_graph.links[selected].style.color);
+        ui.painter().line_segment([end, handle], Stroke::new(1.0, color.gamma_multiply(0.6)));
+        let rect = egui::Rect::from_center_size(handle, egui::Vec2::splat(HANDLE_RADIUS * 2.5));
+        let response = ui.interact(rect, ui.id().with(("link_handle", selected, index)), Sense::drag());
+        let hovered = response.hovered() || response.dragged();
+        ui.painter().circle(handle, HANDLE_RADIUS, if hovered { color } else { Color32::WHITE }, Stroke::new(1.5, color));
+        if response.dragged() {
+            if let Some(pointer) = response.interact_pointer_pos() {
+                node_graph.set_link_control(selected, index, (transform.inverse() * pointer).into());
+            }
+        }
+    }
+    if !ui.ctx().wants_keyboard_input() && ui.input(|i| i.key_pressed(Key::Delete)) {
+        node_graph.remove_link(selected);
+        graph_state.selected_link = None;
+    }
+}
+
+/// 编辑一条关系连线的标签和样式，返回是否有修改
+pub fn link_panel(ui: &mut Ui, node_graph: &mut NodeGraph, link_id: LinkId) -> bool {
+    let Some(link) = node_graph.links.get_mut(link_id) else {
+        return false;
+    };
+    let mut changed = false;
+    egui::Grid::new("cross_link").num_columns(2).show(ui, |ui| {
+        ui.label("Label");
+        changed |= ui.text_edit_singleline(&mut link.label).changed();
+        ui.end_row();
+        ui.label("Color");
+        let mut color = Color32::from(link.style.color);
+        if ui.color_edit_button_srgba(&mut color).changed() {
+            link.style.color = color.into();
+            changed = true;
+        }
+        ui.end_row();
+        ui.label("Width");
+        changed |= ui.add(egui::DragValue::new(&mut link.style.width).range(0.5..=10.0).speed(0.1)).changed();
+        ui.end_row();
+        ui.label("Line");
+        ComboBox::from_id_salt("cross_link_kind").selected_text(link.style.kind.name()).show_ui(ui, |ui| {
+            for kind in ConnectorKind::ALL {
+                changed |= ui.selectable_value(&mut link.style.kind, kind, kind.name()).changed();
+            }
+        });
+        ui.end_row();
+        changed |= ui.checkbox(&mut link.style.arrow, "Arrowhead").changed();
+        changed |= ui.checkbox(&mut link.style.dashed, "Dashed").changed();
+        ui.end_row();
+    });
+    changed
+}
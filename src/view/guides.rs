@@ -0,0 +1,82 @@
+//! 拖动节点时的智能参考线：节点的边或中线和附近节点对齐时吸附过去，并画出参考线
+
+use egui::{Color32, Stroke, Ui};
+use crate::model::{NodeGraph, NodeId, Rect, Vector};
+use super::pan_zoom::PanZoom;
+
+/// 小于这个屏幕距离时吸附
+pub const GUIDE_SNAP_DISTANCE: f32 = 6.0;
+const GUIDE_COLOR: Color32 = Color32::from_rgb(255, 0, 255);
+
+//左边、中线、右边
+fn xs(rect: &Rect) -> [f32; 3] {
+    [rect.min.x, rect.center().x, rect.max.x]
+}
+//上边、中线、下边
+fn ys(rect: &Rect) -> [f32; 3] {
+    [rect.min.y, rect.center().y, rect.max.y]
+}
+
+//在 a 和 b 的所有组合里找绝对值最小且不超过 threshold 的差值 b - a
+fn nearest_offset(a: [f32; 3], b: [f32; 3], threshold: f32, best: &mut Option<f32>) {
+    for from in a {
+        for to in b {
+            let offset = to - from;
+            if offset.abs() <= threshold && best.is_none_or(|best| offset.abs() < best.abs()) {
+                *best = Some(offset);
+            }
+        }
+    }
+}
+
+//把正在拖动的节点吸附到 others 里对齐的位置，并画出参考线
+pub fn snap_to_guides(node_graph: &mut NodeGraph, dragged: NodeId, others: &[NodeId], ui: &Ui, pan_zoom: &PanZoom) {
+    let Some(rect) = node_graph.nodes.get(dragged).map(|node| node.rect()) else {
+        return;
+    };
+    let threshold = GUIDE_SNAP_DISTANCE / pan_zoom.transform.scaling;
+    let others: Vec<Rect> = others.iter()
+        .filter(|id| **id != dragged)
+        .filter_map(|id| node_graph.nodes.get(*id).map(|node| node.rect()))
+        .collect();
+
+    let (mut dx, mut dy) = (None, None);
+    for other in &others {
+        nearest_offset(xs(&rect), xs(other), threshold, &mut dx);
+        nearest_offset(ys(&rect), ys(other), threshold, &mut dy);
+    }
+    if dx.is_none() && dy.is_none() {
+        return;
+    }
+    node_graph.move_node(dragged, Vector::new(dx.unwrap_or(0.0), dy.unwrap_or(0.0)));
+    let rect = node_graph.nodes[dragged].rect();
+
+    //每条对齐的线从拖动的节点画到最远的对齐节点
+    let stroke = Stroke::new(1.0, GUIDE_COLOR);
+    let painter = ui.painter();
+    let transform = pan_zoom.transform;
+    if dx.is_some() {
+        for x in xs(&rect) {
+            let span = others.iter()
+                .filter(|other| xs(other).iter().any(|ox| (ox - x).abs() < 0.01))
+                .fold(rect, |span, other| span.union(*other));
+            if span != rect {
+                let from = transform * egui::pos2(x, span.min.y);
+                let to = transform * egui::pos2(x, span.max.y);
+                painter.line_segment([from, to], stroke);
+            }
+        }
+    }
+    if dy.is_some() {
+        for y in ys(&rect) {
+            let span = others.iter()
+                .filter(|other| ys(other).iter().any(|oy| (oy - y).abs() < 0.01))
+                .fold(rect, |span, other| span.union(*other));
+            if span != rect {
+                let from = transform * egui::pos2(span.min.x, y);
+                let to = transform * egui::pos2(span.max.x, y);
+                painter.line_segment([from, to], stroke);
+            }
+        }
+    }
+}
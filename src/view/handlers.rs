@@ -1,47 +1,59 @@
-use crate::node_graph::{NodeGraph, GraphState, GraphResponse,NodeId};
-use crate::node::{NodeResponse, ButtonResponse,NodeState,ButtonState};
-use slotmap::SlotMap;
-use crate::traits::*;
+use crate::model::{NodeGraph, NodeId};
+use super::node_graph::{GraphState, GraphResponse};
+use super::node::{NodeResponse, ButtonResponse,NodeState,ButtonState};
+use slotmap::SecondaryMap;
+use super::mind_map::MindMapEvent;
 pub fn handle_graph_response(
     node_graph: &mut NodeGraph,
     graph_state: &mut GraphState,
     graph_response: GraphResponse
-) -> Result<(), String> {
-    handle_node_responses(node_graph, graph_state, &graph_response.nodes_response)?;
-    handle_button_responses(node_graph, graph_state, &graph_response.buttons_response)?;
-    Ok(())
+) -> Vec<MindMapEvent> {
+    let mut events = Vec::new();
+    handle_node_responses(node_graph, graph_state, &graph_response.nodes_response, &mut events);
+    handle_button_responses(node_graph, graph_state, &graph_response.buttons_response, &mut events);
+    events
 }
 
 fn handle_node_responses(
     node_graph: &mut NodeGraph,
     graph_state: &mut GraphState,
-    nodes_response: &SlotMap<NodeId, NodeResponse>
-) -> Result<(), String> {
+    nodes_response: &SecondaryMap<NodeId, NodeResponse>,
+    events: &mut Vec<MindMapEvent>,
+) {
     let mut nodes_to_remove = Vec::new();//要删除的节点
     let mut nodes_to_add = Vec::new();//需要新增的节点
-    for (nodeid,response) in nodes_response{
+    for response in nodes_response.values() {
         match response {
             NodeResponse::EditNode(id) => {
+                if graph_state.node_state[*id]!=NodeState::Editing {
+                    events.push(MindMapEvent::EditStarted(*id));//编辑中每帧都会返回 EditNode，只在开始时通知
+                }
                 graph_state.node_state[*id]=NodeState::Editing;
             }
             NodeResponse::AddNode(id)=> {
                 nodes_to_add.push(*id); // 收集要新增的节点
             }
             NodeResponse::Selected (id)=> {
+                events.push(MindMapEvent::NodeSelected(*id));
                 graph_state.node_state[*id]=NodeState::Selected;
             }
             NodeResponse::UnSelected(id) => {
+                match graph_state.node_state[*id] {
+                    NodeState::Editing => events.push(MindMapEvent::EditFinished(*id)),
+                    NodeState::Selected => events.push(MindMapEvent::NodeDeselected(*id)),
+                    _ => {}
+                }
                 graph_state.node_state[*id]=NodeState::UnSelected;
             }
             NodeResponse::InvisibleNode(id)=>{
                 graph_state.node_state[*id]=NodeState::Invisible;
+                graph_state.fold_changed();
             }
             NodeResponse::VisibleNode(id)=>{
                 graph_state.node_state[*id]=NodeState::UnSelected;
+                graph_state.fold_changed();
             }
             NodeResponse::DeleteNode(id)=>{
-                let children = node_graph.query_all_children_nodes(*id);
-                nodes_to_remove.extend(children); 
                 nodes_to_remove.push(*id); // 收集要删除的节点,删除时 要同时删除该节点下的 所有 子节点
             }
             _ => {}
@@ -49,56 +61,50 @@ fn handle_node_responses(
     }
     //新增节点
     for id in nodes_to_add {
-        let new_node = node_graph.add_node_with_father_node(id.clone());
-        graph_state
-            .node_state
-            .insert_with_key(|mut k| {
-            k = new_node;
-            NodeState::UnSelected
-        }); // 插入新的节点状态
-        graph_state.graph_button_state.insert_with_key(|mut k| {
-            k = new_node;
-            ButtonState::UnFold
-        }); // 插入新的节点 按钮状态
+        let new_node = graph_state.add_node_with_father_node(node_graph, id); // 同时插入新的节点状态和按钮状态
+        events.push(MindMapEvent::NodeAdded(new_node));
     }
 
     //删除节点
     for id in nodes_to_remove {
-        graph_state.node_state.remove(id); // 删除节点状态
-        graph_state.graph_button_state.remove(id);// 删除节点按钮状态
-        node_graph.nodes.remove(id);//移除节点
+        for removed in graph_state.remove_node(node_graph, id) {//移除节点和所有子节点，同时删除它们的状态
+            events.push(MindMapEvent::NodeDeleted(removed));
+        }
     }
-    Ok(())
 }
 
 fn handle_button_responses(
     node_graph: &mut NodeGraph,
     graph_state: &mut GraphState,
-    buttons_response: &SlotMap<NodeId, ButtonResponse>
-) -> Result<(), String> {
-    for (node_id, response) in buttons_response {
+    buttons_response: &SecondaryMap<NodeId, ButtonResponse>,
+    events: &mut Vec<MindMapEvent>,
+) {
+    for response in buttons_response.values() {
         //根据 button 返回 button response的父级id 去查询 子级id的列表,并把所有子级 response 设为隐藏
         
         match response {
             ButtonResponse::FoldNode(father_id) => {
                // println!("the button response is {:?}",father_id);
                 graph_state.graph_button_state[*father_id]=ButtonState::Fold;
+                events.push(MindMapEvent::Folded(*father_id));
                 
                 let children = node_graph.query_all_children_nodes(*father_id);
                 for child_id in children {
                     graph_state.node_state[child_id] = NodeState::Invisible;
                 }
+                graph_state.fold_changed();
             },
             ButtonResponse::UnfoldNode(father_id) => {
               //  println!("the button response is {:?}",father_id);
                 graph_state.graph_button_state[*father_id]=ButtonState::UnFold;
+                events.push(MindMapEvent::Unfolded(*father_id));
                 let children = node_graph.query_all_children_nodes(*father_id);
                 for child_id in children {
                     graph_state.node_state[child_id] = NodeState::UnSelected;
                 }
+                graph_state.fold_changed();
             },
             ButtonResponse::None => {},
         }
     }
-    Ok(())
 }
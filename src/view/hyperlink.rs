@@ -0,0 +1,155 @@
+//! 节点上的超链接：右上角的链接图标，点击打开网址、文件或者把镜头移到目标节点
+
+use std::path::Path;
+use egui::{Color32, ComboBox, CursorIcon, Id, OpenUrl, Rect, Sense, Stroke, Ui, Vec2};
+use crate::model::{Hyperlink, NodeGraph, NodeId};
+use super::node_graph::{on_screen_items, GraphState};
+use super::pan_zoom::PanZoom;
+use super::settings::ViewSettings;
+
+//图标的大小（世界坐标）
+const ICON_SIZE: f32 = 10.0;
+//失效链接的图标颜色
+const BROKEN_COLOR: Color32 = Color32::from_rgb(220, 50, 50);
+
+//在屏幕内可见的、有超链接的节点右上角画链接图标（有备注时在备注图标左边），返回点击了图标的节点
+//缩得太小不画文字时不画图标，深层节点开始合并成色块后也不给它们画图标
+pub fn draw_hyperlinks(ui: &Ui, node_graph: &NodeGraph, graph_state: &GraphState, pan_zoom: &PanZoom, settings: &ViewSettings) -> Option<NodeId> {
+    let lod = settings.lod(pan_zoom.transform.scaling);
+    if lod.text <= 0.0 {
+        return None;
+    }
+    let scaling = pan_zoom.transform.scaling;
+    let summary_depth = settings.lod_settings.summary_depth;
+    let mut clicked = None;
+    let (nodes, _) = on_screen_items(node_graph, ui, pan_zoom, graph_state);
+    for id in nodes {
+        let node = &node_graph.nodes[id];
+        let Some(hyperlink) = &node.hyperlink else { continue };
+        if lod.summary > 0.0 && node_graph.node_depth(id) > summary_depth {
+            continue;
+        }
+        let rect = Rect::from(node.rect());
+        let rect = Rect::from_min_max(pan_zoom.transform * rect.min, pan_zoom.transform * rect.max);
+        //备注图标宽 8，右边留 4
+        let right = if node.note.is_empty() { 4.0 } else { 16.0 };
+        let icon = Rect::from_min_size(rect.right_top() + Vec2::new(-(right + ICON_SIZE), 4.0) * scaling, Vec2::splat(ICON_SIZE * scaling));
+        let color = if node_graph.is_link_broken(id) {
+            BROKEN_COLOR
+        } else {
+            Color32::from(node_graph.node_style(id).text_color)
+        };
+        paint_link_icon(ui, icon, color.gamma_multiply(lod.text), scaling);
+        let response = ui.interact(icon, Id::new(("hyperlink", id)), Sense::click())
+            .on_hover_cursor(CursorIcon::PointingHand)
+            .on_hover_text(hyperlink.describe(node_graph));
+        if response.clicked() {
+            clicked = Some(id);
+        }
+    }
+    clicked
+}
+
+//一个方框加一个指向右上角的箭头
+fn paint_link_icon(ui: &Ui, icon: Rect, color: Color32, scaling: f32) {
+    let stroke = Stroke::new(scaling.clamp(0.5, 1.5), color);
+    let painter = ui.painter();
+    let corner = icon.width() * 0.45;
+    painter.line_segment([icon.left_top() + Vec2::new(corner, 0.0), icon.left_top()], stroke);
+    painter.line_segment([icon.left_top(), icon.left_bottom()], stroke);
+    painter.line_segment([icon.left_bottom(), icon.right_bottom()], stroke);
+    painter.line_segment([icon.right_bottom(), icon.right_bottom() - Vec2::new(0.0, corner)], stroke);
+    let tip = icon.right_top();
+    painter.line_segment([icon.center(), tip], stroke);
+    painter.line_segment([tip, tip - Vec2::new(corner, 0.0)], stroke);
+    painter.line_segment([tip, tip + Vec2::new(0.0, corner)], stroke);
+}
+
+//打开节点的超链接：网址和文件交给系统打开，本导图里的节点选中并把镜头移过去
+//base_dir 是相对路径的起点；指向其它导图的链接返回 false，由宿主程序读取那个文件
+pub fn follow_link(ui: &Ui, node_graph: &NodeGraph, graph_state: &mut GraphState, pan_zoom: &mut PanZoom, node_id: NodeId, base_dir: &Path) -> bool {
+    let Some(hyperlink) = node_graph.nodes.get(node_id).and_then(|node| node.hyperlink.as_ref()) else {
+        return false;
+    };
+    if node_graph.is_link_broken(node_id) {
+        return false;
+    }
+    match hyperlink {
+        Hyperlink::Url(url) => ui.ctx().open_url(OpenUrl::new_tab(url.trim())),
+        Hyperlink::File(path) => {
+            let Some(url) = Hyperlink::file_url(&Hyperlink::resolve(base_dir, path)) else { return false };
+            ui.ctx().open_url(OpenUrl::new_tab(url));
+        }
+        Hyperlink::Node { file: None, node } => {
+            graph_state.focus_node(node_graph, *node);
+            pan_zoom.center_on(node_graph.nodes[*node].node_pos);
+        }
+        Hyperlink::Node { file: Some(_), .. } => return false,
+    }
+    true
+}
+
+//编辑节点的超链接，targets 是可以链接的节点和它们的文字（本导图或链接里指定的导图），有修改时返回 true
+pub fn hyperlink_panel(ui: &mut Ui, node_graph: &mut NodeGraph, node_id: NodeId, targets: &[(NodeId, String)]) -> bool {
+    let broken = node_graph.is_link_broken(node_id);
+    let Some(node) = node_graph.nodes.get_mut(node_id) else {
+        return false;
+    };
+    let mut changed = false;
+    egui::Grid::new("hyperlink").num_columns(2).show(ui, |ui| {
+        ui.label("Kind");
+        let current = node.hyperlink.as_ref().map_or("none", Hyperlink::kind);
+        ComboBox::from_id_salt("hyperlink_kind").selected_text(current).show_ui(ui, |ui| {
+            for kind in std::iter::once("none").chain(Hyperlink::KINDS) {
+                if ui.selectable_label(kind == current, kind).clicked() && kind != current {
+                    node.hyperlink = match kind {
+                        "url" => Some(Hyperlink::Url(String::new())),
+                        "file" => Some(Hyperlink::File(String::new())),
+                        "node" => Some(Hyperlink::Node { file: None, node: targets.first().map(|(id, _)| *id).unwrap_or_default() }),
+                        _ => None,
+                    };
+                    changed = true;
+                }
+            }
+        });
+        ui.end_row();
+        match &mut node.hyperlink {
+            Some(Hyperlink::Url(target)) | Some(Hyperlink::File(target)) => {
+                ui.label("Target");
+                changed |= ui.text_edit_singleline(target).changed();
+                ui.end_row();
+            }
+            Some(Hyperlink::Node { file, node }) => {
+                ui.label("Map");
+                let mut path = file.clone().unwrap_or_default();
+                if ui.add(egui::TextEdit::singleline(&mut path).hint_text("this map")).changed() {
+                    *file = (!path.trim().is_empty()).then_some(path);
+                    changed = true;
+                }
+                ui.end_row();
+                ui.label("Node");
+                let selected = targets.iter().find(|(id, _)| id == node).map_or("missing", |(_, text)| text.as_str());
+                ComboBox::from_id_salt("hyperlink_node").selected_text(selected).show_ui(ui, |ui| {
+                    for (id, text) in targets {
+                        changed |= ui.selectable_value(node, *id, text.as_str()).changed();
+                    }
+                });
+                ui.end_row();
+            }
+            None => {}
+        }
+    });
+    if broken {
+        ui.colored_label(BROKEN_COLOR, "The link target is missing");
+    }
+    changed
+}
+
+//导图里可以作为链接目标的节点：id 和第一行文字，按 id 排序
+pub fn link_targets(node_graph: &NodeGraph) -> Vec<(NodeId, String)> {
+    let mut targets: Vec<(NodeId, String)> = node_graph.nodes.iter()
+        .map(|(id, node)| (id, node.node_text.lines().next().unwrap_or_default().to_owned()))
+        .collect();
+    targets.sort_unstable_by_key(|(id, _)| *id);
+    targets
+}
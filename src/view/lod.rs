@@ -0,0 +1,93 @@
+//! 缩小时的细节层级：文字淡出、曲线拉直、深层子树合并成一个色块
+//!
+//! 每一级都有一段过渡区间，在区间里按缩放比例平滑插值，避免缩放时画面突然跳变。
+
+/// 各级细节开始生效的缩放比例
+#[derive(Debug, Clone, Copy)]
+pub struct LodSettings {
+    /// 低于这个缩放比例不画文字，节点只画成色块
+    pub text_scale: f32,
+    /// 低于这个缩放比例贝塞尔曲线变成直线
+    pub curve_scale: f32,
+    /// 低于这个缩放比例，比 summary_depth 更深的子树合并成一个色块
+    pub summary_scale: f32,
+    /// 合并子树时保留的层级，根节点为 1
+    pub summary_depth: usize,
+    /// 过渡区间占阈值的比例，比如 0.3 表示从 0.7 * 阈值 到 阈值 之间渐变
+    pub fade: f32,
+}
+
+impl Default for LodSettings {
+    fn default() -> Self {
+        Self {
+            text_scale: 0.45,
+            curve_scale: 0.3,
+            summary_scale: 0.15,
+            summary_depth: 2,
+            fade: 0.3,
+        }
+    }
+}
+
+/// 当前缩放比例下每一级细节的程度，都在 0..=1 之间
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lod {
+    /// 文字不透明度，0 时完全不做文字排版
+    pub text: f32,
+    /// 曲线弯曲程度，0 时画直线
+    pub curve: f32,
+    /// 子树色块不透明度，1 时深层节点不再单独绘制
+    pub summary: f32,
+}
+
+impl Lod {
+    pub const FULL: Lod = Lod { text: 1.0, curve: 1.0, summary: 0.0 };
+}
+
+impl LodSettings {
+    pub fn lod(&self, scaling: f32) -> Lod {
+        Lod {
+            text: self.ramp(scaling, self.text_scale),
+            curve: self.ramp(scaling, self.curve_scale),
+            summary: 1.0 - self.ramp(scaling, self.summary_scale),
+        }
+    }
+
+    //缩放比例从 threshold * (1 - fade) 增大到 threshold 时，从 0 平滑过渡到 1
+    fn ramp(&self, scaling: f32, threshold: f32) -> f32 {
+        let start = threshold * (1.0 - self.fade);
+        if threshold <= start {
+            return if scaling >= threshold { 1.0 } else { 0.0 };
+        }
+        let t = ((scaling - start) / (threshold - start)).clamp(0.0, 1.0);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn details_fade_out_as_the_map_shrinks() {
+        let settings = LodSettings::default();
+        assert_eq!(settings.lod(1.0), Lod::FULL);
+        assert_eq!(settings.lod(0.01), Lod { text: 0.0, curve: 0.0, summary: 1.0 });
+        //过渡区间里平滑变化，缩得越小细节越少
+        let middle = settings.lod(settings.text_scale * (1.0 - settings.fade / 2.0));
+        assert!((middle.text - 0.5).abs() < 1e-4);
+        let mut last = Lod::FULL;
+        for i in (1..=100).rev() {
+            let lod = settings.lod(i as f32 / 100.0);
+            assert!(lod.text <= last.text && lod.curve <= last.curve && lod.summary >= last.summary);
+            last = lod;
+        }
+    }
+
+    #[test]
+    fn zero_fade_switches_at_the_threshold() {
+        let settings = LodSettings { fade: 0.0, ..LodSettings::default() };
+        assert_eq!(settings.lod(settings.text_scale).text, 1.0);
+        assert_eq!(settings.lod(settings.text_scale - 0.001).text, 0.0);
+    }
+}
@@ -0,0 +1,194 @@
+//! 可以嵌入到任意 egui 界面里的思维导图控件
+//!
+//! ```ignore
+//! let response = MindMap::new(&mut node_graph, &mut graph_state, &mut pan_zoom).show(ui);
+//! for event in &response.events {
+//!     println!("{event:?}");
+//! }
+//! ```
+
+use std::path::PathBuf;
+use egui::{Color32, Key, Response, Rounding, Sense, Ui, UiBuilder, Widget};
+use eframe::epaint::Pos2;
+use crate::model::{NodeGraph, NodeId};
+use super::boundary::select_boundary;
+use super::connector_label::{connector_at, edit_connector_label, start_editing};
+use super::cross_link::draw_cross_links;
+use super::handlers::handle_graph_response;
+use super::hyperlink::{draw_hyperlinks, follow_link};
+use super::minimap::Minimap;
+use super::node_graph::GraphState;
+use super::pan_zoom::PanZoom;
+use super::settings::ViewSettings;
+use super::traits::NodeGraphs;
+
+/// 控件一帧内发生的用户操作，方便宿主程序同步自己的数据
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MindMapEvent {
+    NodeAdded(NodeId),
+    NodeDeleted(NodeId),
+    NodeSelected(NodeId),
+    NodeDeselected(NodeId),
+    EditStarted(NodeId),
+    EditFinished(NodeId),
+    Folded(NodeId),
+    Unfolded(NodeId),
+    /// 点击了节点的超链接图标；指向其它导图文件的链接需要宿主程序自己打开
+    LinkOpened(NodeId),
+}
+
+pub struct MindMapResponse {
+    /// 整个画布区域的响应
+    pub response: Response,
+    pub events: Vec<MindMapEvent>,
+}
+
+/// 思维导图控件，数据由调用方持有，控件每帧只借用
+pub struct MindMap<'a> {
+    node_graph: &'a mut NodeGraph,
+    graph_state: &'a mut GraphState,
+    pan_zoom: &'a mut PanZoom,
+    desired_size: Option<egui::Vec2>,
+    minimap: Option<Minimap>,
+    settings: ViewSettings,
+    base_dir: PathBuf,
+}
+
+impl<'a> MindMap<'a> {
+    pub fn new(node_graph: &'a mut NodeGraph, graph_state: &'a mut GraphState, pan_zoom: &'a mut PanZoom) -> Self {
+        Self { node_graph, graph_state, pan_zoom, desired_size: None, minimap: Some(Minimap::default()), settings: ViewSettings::default(), base_dir: PathBuf::from(".") }
+    }
+    //控件大小，默认占满剩余空间
+    pub fn desired_size(mut self, size: egui::Vec2) -> Self {
+        self.desired_size = Some(size);
+        self
+    }
+    //角落里的缩略图，传 None 不显示
+    pub fn minimap(mut self, minimap: Option<Minimap>) -> Self {
+        self.minimap = minimap;
+        self
+    }
+    //网格、参考线、文字排版和细节层级等显示设置
+    pub fn settings(mut self, settings: ViewSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+    //超链接里相对路径的起点，一般是导图文件所在的目录，默认是当前目录
+    pub fn base_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.base_dir = dir.into();
+        self
+    }
+
+    pub fn show(mut self, ui: &mut Ui) -> MindMapResponse {
+        let size = self.desired_size.unwrap_or_else(|| ui.available_size());
+        let (rect, mut response) = ui.allocate_exact_size(size, Sense::click_and_drag());
+        let mut events = Vec::new();
+        //文档可能在控件外被修改（比如读取文件），先把状态和文档对齐；概要节点跟着括起来的节点移动
+        self.node_graph.layout_summaries();
+        self.graph_state.sync(self.node_graph);
+        self.pan_zoom.viewport = rect;
+
+        //在子 ui 里绘制，超出控件区域的节点会被裁剪，不影响宿主的其它界面
+        let mut canvas_ui = ui.new_child(UiBuilder::new().max_rect(rect));
+        canvas_ui.set_clip_rect(rect.intersect(ui.clip_rect()));
+        canvas_ui.painter().rect_filled(rect, Rounding::default(), Color32::from(self.node_graph.theme.background));
+        canvas_ui.painter().rect_stroke(rect, Rounding::default(), ui.ctx().style().visuals.window_stroke);
+
+        self.pan_zoom(&canvas_ui, &response, &mut events);
+        //推进镜头动画，要放在处理输入之后，这一帧新开始的动画也会请求下一帧
+        self.pan_zoom.animate(ui.ctx());
+        if self.settings.grid.visible {
+            self.settings.grid.draw(&canvas_ui, self.pan_zoom, Color32::from(self.node_graph.theme.grid));
+        }
+        let graph_response = self.node_graph.draw(&mut canvas_ui, self.pan_zoom, &self.settings, self.graph_state);
+        draw_cross_links(&mut canvas_ui, self.node_graph, self.graph_state, self.pan_zoom, &response);
+        select_boundary(&canvas_ui, self.node_graph, self.graph_state, self.pan_zoom, &response);
+        edit_connector_label(&mut canvas_ui, self.node_graph, self.graph_state, self.pan_zoom, &self.settings);
+        if let Some(node_id) = draw_hyperlinks(&canvas_ui, self.node_graph, self.graph_state, self.pan_zoom, &self.settings) {
+            follow_link(&canvas_ui, self.node_graph, self.graph_state, self.pan_zoom, node_id, &self.base_dir);
+            events.push(MindMapEvent::LinkOpened(node_id));
+        }
+        events.extend(handle_graph_response(self.node_graph, self.graph_state, graph_response));
+        if let Some(minimap) = &self.minimap {
+            minimap.show(&mut canvas_ui, rect, self.node_graph, self.graph_state, self.pan_zoom);
+        }
+        if !events.is_empty() {
+            response.mark_changed();
+        }
+        MindMapResponse { response, events }
+    }
+
+    //整个画布的缩放和平移，双击空白处新增节点，双击连线编辑连线上的文字
+    fn pan_zoom(&mut self, ui: &Ui, response: &Response, events: &mut Vec<MindMapEvent>) {
+        if response.dragged() {
+            self.pan_zoom.pan(response.drag_delta());
+        }
+
+        if let Some(pointer) = ui.ctx().input(|i| i.pointer.hover_pos()) {
+            if response.hovered() {
+                let zoom_delta = ui.ctx().input(|i| i.zoom_delta());
+                if zoom_delta != 1.0 {
+                    // Zoom in on pointer:
+                    self.pan_zoom.zoom_around(zoom_delta, pointer);
+                }
+            }
+        }
+        self.zoom_shortcuts(ui);
+        if response.double_clicked() {
+            if let Some(pos) = ui.ctx().pointer_hover_pos() {
+                //双击连线编辑连线上的文字，双击空白处新增节点
+                if let Some(node_id) = connector_at(self.node_graph, self.graph_state, self.pan_zoom, &self.settings, pos) {
+                    start_editing(ui, self.graph_state, node_id);
+                    return;
+                }
+                //这里要 inverse transform  因为node draw时 要用加 pan ，这里的pos 要还原成 原始左边，不然绘制会加2遍 transform
+                let node_id = self.add_node(self.pan_zoom.transform.inverse() * pos);
+                events.push(MindMapEvent::NodeAdded(node_id));
+            }
+        }
+    }
+
+    //缩放快捷键：+/- 以视口中心缩放，0 恢复 100%，Shift+1 显示整个导图，Shift+2 显示选中的节点
+    //正在编辑文字时不处理，避免和输入冲突
+    fn zoom_shortcuts(&mut self, ui: &Ui) {
+        if ui.ctx().wants_keyboard_input() {
+            return;
+        }
+        let (zoom_in, zoom_out, reset, fit_all, fit_selection) = ui.ctx().input(|i| {
+            let plain = i.modifiers.is_none() || i.modifiers.shift_only();
+            (
+                plain && (i.key_pressed(Key::Plus) || i.key_pressed(Key::Equals)),
+                plain && i.key_pressed(Key::Minus),
+                i.modifiers.is_none() && i.key_pressed(Key::Num0),
+                i.modifiers.shift_only() && i.key_pressed(Key::Num1),
+                i.modifiers.shift_only() && i.key_pressed(Key::Num2),
+            )
+        });
+        if zoom_in {
+            self.pan_zoom.zoom_in();
+        }
+        if zoom_out {
+            self.pan_zoom.zoom_out();
+        }
+        if reset {
+            self.pan_zoom.reset_zoom();
+        }
+        if fit_all {
+            self.pan_zoom.zoom_to_fit(self.node_graph);
+        }
+        if fit_selection {
+            self.pan_zoom.zoom_to_selection(self.node_graph, self.graph_state);
+        }
+    }
+
+    fn add_node(&mut self, pos: Pos2) -> NodeId {
+        //节点和它的状态一起创建
+        self.graph_state.add_node_with_position(self.node_graph, pos.into())
+    }
+}
+
+impl Widget for MindMap<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        self.show(ui).response
+    }
+}
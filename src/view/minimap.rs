@@ -0,0 +1,159 @@
+//! 画布角落里的缩略图：显示整个导图和当前视口的位置，点击或拖动缩略图可以移动主视图
+
+use std::collections::BTreeMap;
+use egui::emath::RectTransform;
+use egui::{Align2, Color32, Mesh, Pos2, Rect, Response, Rounding, Sense, Stroke, Ui, Vec2};
+use crate::model::{NodeGraph, Theme};
+use super::node_graph::{is_node_visible, CacheKey, GraphState};
+use super::pan_zoom::PanZoom;
+
+/// 缩略图的大小和位置
+#[derive(Debug, Clone, Copy)]
+pub struct Minimap {
+    /// 缩略图在屏幕上的大小
+    pub size: Vec2,
+    /// 放在画布的哪个角
+    pub anchor: Align2,
+    /// 和画布边缘的距离
+    pub margin: f32,
+}
+
+impl Default for Minimap {
+    fn default() -> Self {
+        Self {
+            size: Vec2::new(200.0, 140.0),
+            anchor: Align2::RIGHT_BOTTOM,
+            margin: 10.0,
+        }
+    }
+}
+
+impl Minimap {
+    //在 canvas 的角落画缩略图，要在导图之后调用，这样缩略图盖在节点上面并优先响应鼠标
+    pub fn show(&self, ui: &mut Ui, canvas: Rect, node_graph: &NodeGraph, graph_state: &mut GraphState, pan_zoom: &mut PanZoom) -> Option<Response> {
+        let rect = self.anchor.align_size_within_rect(self.size, canvas.shrink(self.margin));
+        let inner = rect.shrink(4.0);
+        //更新缓存时要读取 graph_state 里的折叠状态，先把缓存取出来
+        let mut cache = std::mem::take(&mut graph_state.minimap_cache);
+        cache.update(node_graph, graph_state, inner.size());
+        graph_state.minimap_cache = cache;
+        let cache = &graph_state.minimap_cache;
+        let world = cache.world?;
+        let response = ui.interact(rect, ui.id().with("minimap"), Sense::click_and_drag());
+        let to_screen = RectTransform::from_to(world, inner);
+
+        let painter = ui.painter().with_clip_rect(rect);
+        let visuals = ui.visuals();
+        painter.rect(rect, Rounding::same(4.0), visuals.extreme_bg_color.gamma_multiply(0.9), visuals.window_stroke);
+        let mut mesh = cache.mesh.clone();
+        mesh.translate(inner.min.to_vec2());
+        painter.add(mesh);
+        let viewport = to_screen.transform_rect(pan_zoom.transform.inverse() * pan_zoom.viewport);
+        painter.rect_stroke(viewport, Rounding::ZERO, Stroke::new(1.5, visuals.selection.stroke.color));
+
+        //点击时平滑移动过去，拖动时跟着鼠标立即移动
+        if let Some(pointer) = response.interact_pointer_pos() {
+            let world_pos = to_screen.inverse().transform_pos(pointer.clamp(inner.min, inner.max));
+            if response.dragged() {
+                pan_zoom.set_center(world_pos.into());
+            } else if response.clicked() {
+                pan_zoom.center_on(world_pos.into());
+            }
+        }
+        Some(response)
+    }
+}
+
+/// 缩略图上的节点色块，节点的位置、颜色、折叠状态和缩略图的大小都没变时重用上一帧的结果
+#[derive(Default)]
+pub struct MinimapCache {
+    //导图内容的版本和缩略图的大小
+    key: Option<(CacheKey, Vec2)>,
+    theme: Option<Theme>,
+    //缩略图显示的世界坐标范围，已经按缩略图的宽高比扩大，没有节点时为 None
+    world: Option<Rect>,
+    //以缩略图内部左上角为原点的色块
+    mesh: Mesh,
+}
+
+impl MinimapCache {
+    fn update(&mut self, node_graph: &NodeGraph, graph_state: &GraphState, size: Vec2) {
+        let key = Some((graph_state.cache_key(node_graph), size));
+        if key == self.key && self.theme.as_ref() == Some(&node_graph.theme) {
+            return;
+        }
+        self.key = key;
+        self.theme = Some(node_graph.theme.clone());
+        self.mesh.clear();
+        //世界坐标的范围按缩略图的宽高比扩大，保证横竖缩放一致
+        self.world = node_graph.bounds().map(|world| fit_aspect(Rect::from(world).expand(20.0), size.x / size.y));
+        let Some(world) = self.world else { return };
+        let to_local = RectTransform::from_to(world, Rect::from_min_size(Pos2::ZERO, size));
+        //不到一个像素的节点合并成所在像素的一个色块，大导图缩小后色块数不会超过缩略图的像素数
+        let mut pixels = BTreeMap::new();
+        for (id, node) in &node_graph.nodes {
+            if !is_node_visible(node_graph, id, graph_state) {
+                continue;
+            }
+            let node_rect = to_local.transform_rect(node.rect().into());
+            let color = Color32::from(node_graph.node_style(id).fill);
+            if node_rect.width() < 1.0 && node_rect.height() < 1.0 {
+                let pixel = node_rect.center().floor();
+                pixels.insert((pixel.x as i32, pixel.y as i32), color);
+            } else {
+                self.mesh.add_colored_rect(node_rect, color);
+            }
+        }
+        for ((x, y), color) in pixels {
+            self.mesh.add_colored_rect(Rect::from_min_size(Pos2::new(x as f32, y as f32), Vec2::splat(1.0)), color);
+        }
+    }
+}
+
+//以中心为准扩大矩形，使宽高比等于 aspect
+fn fit_aspect(rect: Rect, aspect: f32) -> Rect {
+    let size = if rect.aspect_ratio() > aspect {
+        Vec2::new(rect.width(), rect.width() / aspect)
+    } else {
+        Vec2::new(rect.height() * aspect, rect.height())
+    };
+    Rect::from_center_size(rect.center(), size.max(Vec2::splat(1.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Point;
+    use crate::view::node::NodeState;
+
+    #[test]
+    fn fit_aspect_grows_around_the_center() {
+        let rect = Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(100.0, 10.0));
+        let fitted = fit_aspect(rect, 2.0);
+        assert_eq!(fitted.center(), rect.center());
+        assert_eq!(fitted.size(), Vec2::new(100.0, 50.0));
+        assert_eq!(fit_aspect(rect, 20.0).size(), Vec2::new(200.0, 10.0));
+        //只有一个点时也有大小
+        assert!(fit_aspect(Rect::from_min_max(Pos2::ZERO, Pos2::ZERO), 1.5).is_positive());
+    }
+
+    #[test]
+    fn cache_is_rebuilt_only_when_the_map_or_folding_changes() {
+        let mut graph = NodeGraph::default();
+        let root = graph.add_node_with_position(Point::ZERO);
+        let child = graph.add_node_with_father_node(root);
+        let mut graph_state = GraphState::from_graph(&graph);
+        let mut cache = MinimapCache::default();
+        let size = Vec2::new(200.0, 140.0);
+        cache.update(&graph, &graph_state, size);
+        assert_eq!(cache.mesh.vertices.len(), 8);
+        //内容没变时不重新生成，清空的色块不会回来
+        cache.mesh.clear();
+        cache.update(&graph, &graph_state, size);
+        assert!(cache.mesh.vertices.is_empty());
+        graph_state.node_state[child] = NodeState::Invisible;
+        graph_state.fold_changed();
+        cache.update(&graph, &graph_state, size);
+        assert_eq!(cache.mesh.vertices.len(), 4);
+    }
+}
@@ -0,0 +1,27 @@
+//! egui 界面层：把 `model` 里的文档画出来，并把用户的操作转换成对模型的修改
+
+pub mod boundary;
+pub mod canvas;
+pub mod connector_label;
+pub mod convert;
+pub mod cross_link;
+pub mod guides;
+pub mod handlers;
+pub mod hyperlink;
+pub mod lod;
+pub mod mind_map;
+pub mod minimap;
+pub mod node;
+pub mod node_graph;
+pub mod note;
+pub mod pan_zoom;
+pub mod settings;
+pub mod style_panel;
+pub mod summary;
+pub mod traits;
+
+pub use mind_map::{MindMap, MindMapEvent, MindMapResponse};
+pub use minimap::Minimap;
+pub use node_graph::GraphState;
+pub use pan_zoom::PanZoom;
+pub use settings::ViewSettings;
@@ -0,0 +1,428 @@
+use std::sync::Arc;
+use egui::{Align2, FontId};
+use eframe::egui;
+use eframe::epaint::{Color32, Pos2, Rect};
+use egui::{Id, Key, Sense, Shape, Stroke, Style, Ui, Vec2};
+use egui::text::{LayoutJob, TextFormat};
+use egui::Galley;
+use crate::model::{ConnectorStyle, Node, NodeId, NodeShape, Point, ResolvedStyle, TextSpan, Theme, Vector};
+use crate::model::node::BUTTON_SIZE;
+use crate::model::rich_text::parse_lines;
+use crate::model::TextLayout;
+use super::canvas::Canvas;
+use super::pan_zoom::PanZoom;
+use super::settings::ViewSettings;
+use super::traits::View;
+
+//假粗体两次绘制错开的距离，相对字号
+const BOLD_OFFSET: f32 = 0.04;
+//节点文字里链接的颜色
+const LINK_COLOR: Color32 = Color32::from_rgb(40, 110, 220);
+
+#[derive(Debug)]
+pub enum NodeState{
+    Editing,
+    Selected,
+    UnSelected,
+    Hover,
+    Invisible,
+   // Visible  //这个状态有点多余
+}
+#[derive(Debug)]
+pub enum NodeResponse{
+    AddNode(NodeId),
+    DeleteNode(NodeId),
+    Selected(NodeId),
+    UnSelected(NodeId),
+    EditNode(NodeId),
+    InvisibleNode(NodeId),
+    VisibleNode(NodeId), 
+    None,
+}
+#[derive(Debug,PartialEq)]
+pub enum ButtonResponse{
+    None,
+    FoldNode(NodeId),//father node
+    UnfoldNode(NodeId),//father node
+}
+impl View for Node {
+    fn draw(& mut self, ui: &mut Ui, pan_zoom: &mut PanZoom, settings: &ViewSettings, node_state: &mut NodeState, defaults: &ResolvedStyle, theme: &Theme) ->NodeResponse {
+        //先按文字调整大小，这一帧的矩形、连线和按钮位置都用新的大小
+        let text_alpha = settings.lod(pan_zoom.transform.scaling).text;
+        if settings.text_layout.auto_size && (text_alpha > 0.0 || *node_state == NodeState::Editing) {
+            self.fit_text(ui, &settings.text_layout, &self.style(defaults));
+        }
+        let style = self.style(defaults);
+        //这里每次绘制要用新的 transform pos 和size ，不能用 self 的pos 和size ，因为每次循环累计缩放和平移
+        let transformed_pos = pan_zoom.transform* Pos2::from(self.node_pos);
+        let transformed_size = pan_zoom.transform.scaling * Vec2::from(self.node_size);
+        //self.node_pos=pan_zoom.transform* self.node_pos;
+
+        let rect = egui::Rect::from_center_size(transformed_pos, transformed_size);
+       // let node_area=Area::new(Id::new(self.node_id)).order(Order::Middle).default_pos(rect.min).default_size(transformed_size).movable(true);//这里要设置每个node area的 大小 和位置
+
+        // 不绘制矩形，只绘制编辑框
+        match node_state {
+            NodeState::Invisible => {
+                // 不可见状态，不做任何操作
+                return NodeResponse::None;
+            }
+            _ =>{
+                match node_state {
+                    NodeState::Editing => {
+                            // 编辑状态下，绘制编辑框
+                            let mut text_zone_style = Style::default(); // 编辑框样式
+                            text_zone_style.visuals.override_text_color = Some(Color32::from(theme.edit_text)); // 字体颜色
+                            text_zone_style.visuals.extreme_bg_color = Color32::from(theme.edit_background); // 背景颜色
+            
+                            ui.set_style(text_zone_style);
+                            //put 来设置 ui 绘制的位置和矩形大小
+                            let scaling = pan_zoom.transform.scaling;
+                            let wrap_width = self.text_wrap_width(&settings.text_layout) * scaling;
+                            let text_response = ui.put(
+                                    rect,
+                                    egui::TextEdit::multiline(&mut self.node_text)
+                                        .font(FontId::proportional(style.font_size * scaling))
+                                        .desired_rows(1)
+                                        .desired_width(wrap_width),
+                                );
+            
+                            // 当编辑框失去焦点时，退出编辑状态
+                            return if text_response.lost_focus() {
+                                NodeResponse::UnSelected(self.node_id) // 返回对应响应
+                            } else {
+                                NodeResponse::EditNode(self.node_id)
+                            };
+                        }
+                     _ => {
+                            // 非编辑状态时绘制矩形和文本
+                            let response = ui.allocate_rect(rect, Sense::click_and_drag());
+                            // egui 按矩形检测，这里再按节点的外形过滤一次，椭圆、菱形的角上不算点中
+                            let pointer_inside = response.interact_pointer_pos().or(response.hover_pos())
+                                .is_some_and(|p| self.node_shape.contains(rect.into(), p.into()));
+                            let hovered = response.hovered() && pointer_inside;
+                            let clicked = response.clicked() && pointer_inside;
+            
+                            if response.dragged() {
+                                self.drag(ui, pan_zoom, &settings.grid, &response, pointer_inside);
+                            }
+            
+                            let scaling = pan_zoom.transform.scaling;
+                            let border = Stroke::new(style.border_width * scaling, Color32::from(style.border_color));
+                            paint_shape(ui, self.node_shape, rect, Color32::from(style.fill), border, scaling);
+                            // 选中和悬停只在节点外面画一圈高亮，不改节点自己的颜色
+                            let highlight = match node_state {
+                                NodeState::Selected => Some(Color32::from(theme.selection)),
+                                NodeState::Hover => Some(Color32::from(theme.hover)),
+                                _ => None,
+                            };
+                            if let Some(color) = highlight {
+                                paint_shape(ui, self.node_shape, rect.expand(3.0), Color32::TRANSPARENT, Stroke::new(2.0, color), scaling);
+                            }
+            
+                            // 缩得太小时只画色块，不做文字排版
+                            if text_alpha > 0.0 {
+                                let text_layout = &settings.text_layout;
+                                let color = Color32::from(style.text_color).gamma_multiply(text_alpha);
+                                let wrap_width = self.text_wrap_width(&settings.text_layout) * scaling;
+                                let text = self.text_galleys(ui, &style, scaling, wrap_width, color, true);
+                                let galley = text.galley;
+                                // 多行文字整体放在矩形中心
+                                let text_rect = match self.node_shape {
+                                    // 下划线样式的文字贴着线
+                                    NodeShape::Underline => Align2::CENTER_BOTTOM.anchor_size(rect.center_bottom() - Vec2::new(0.0, text_layout.padding.y * scaling), galley.size()),
+                                    _ => Align2::CENTER_CENTER.anchor_size(rect.center(), galley.size()),
+                                };
+                                ui.painter().galley(text_rect.min, galley, color);
+                                if let Some(bold) = text.bold {
+                                    // 默认字体没有粗体，把粗体的部分错开一点再画一遍加粗
+                                    let offset = Vec2::new(style.font_size * scaling * BOLD_OFFSET, 0.0);
+                                    ui.painter().galley(text_rect.min + offset, bold, color);
+                                }
+                                // 有备注时右上角画个小图标，鼠标停在上面显示备注
+                                if !self.note.is_empty() {
+                                    let icon_rect = paint_note_icon(ui, rect, color, scaling);
+                                    if ui.rect_contains_pointer(icon_rect) {
+                                        egui::show_tooltip_text(ui.ctx(), ui.layer_id(), Id::new(("note", self.node_id)), self.note.as_str());
+                                    }
+                                }
+                            }
+        
+                             // 处理状态切换
+                            match node_state {
+                                NodeState::Hover => {
+                                    if !hovered && !response.dragged() {
+                                        *node_state = NodeState::UnSelected;
+                                    }
+            
+                                    if response.double_clicked() && pointer_inside {
+            
+                                        return NodeResponse::EditNode(self.node_id);
+                                    }
+            
+                                    if clicked {
+                                        return NodeResponse::Selected(self.node_id);
+                                    }
+                                }
+                                NodeState::Selected => {
+                                    if clicked {
+                                        return NodeResponse::UnSelected(self.node_id);
+                                    }
+                                    // 在侧边栏里输入备注等文字时，按键不作用于节点
+                                    let typing = ui.ctx().wants_keyboard_input();
+                                    if !typing && ui.ctx().input(|x| {x.key_pressed(Key::Tab)}){
+                                        return  NodeResponse::AddNode(self.node_id)
+                                    }
+                                    if !typing && ui.ctx().input(|x| {x.key_pressed(Key::Delete)}){
+                                        return  NodeResponse::DeleteNode(self.node_id)
+                                    }
+                                }
+                                NodeState::UnSelected if hovered => {
+                                    *node_state = NodeState::Hover;
+                                }
+                                _ => {}
+                            }
+                        }
+                 }
+            }    
+        }
+        NodeResponse::None
+        
+    }
+    fn draw_button(& mut self, ui: &mut Ui, pan_zoom: &mut PanZoom, button_state:&mut ButtonState, defaults: &ResolvedStyle, connector: &ConnectorStyle)->ButtonResponse  {
+        //这里每次绘制要用新的 transform pos 和size ，不能用 self 的pos 和size ，因为每次循环累计缩放和平移
+        let transformed_button_size = pan_zoom.transform.scaling * Vec2::new(BUTTON_SIZE, BUTTON_SIZE);//5是半径
+        let transformed_anchor = pan_zoom.transform* Pos2::from(self.right_anchor());
+        let transformed_button_pos = pan_zoom.transform* Pos2::from(self.button_pos());
+        let rect = Rect::from_center_size(transformed_button_pos, transformed_button_size);
+
+        let fill = self.style(defaults).fill;
+        ui.painter().circle_filled(transformed_button_pos, pan_zoom.transform.scaling*BUTTON_SIZE/2.0, Color32::from(fill));
+        //button 与 node 的连线
+        ui.painter().line_segment(
+            [transformed_anchor, transformed_button_pos-transformed_button_size*Vec2::new(0.5,0.0)],
+            (connector.width, Color32::from(connector.color))
+        );
+
+            match button_state {
+                ButtonState::UnFold => {
+                    ui.painter().line_segment(
+                        [transformed_button_pos + transformed_button_size*Vec2::new(0.5,0.0),transformed_button_pos - transformed_button_size*Vec2::new(0.5,0.0)],
+                        (2.0, Color32::from_white_alpha(255)),
+                    );
+                }
+                ButtonState::Fold => {
+                    ui.painter().line_segment(
+                        [transformed_button_pos + transformed_button_size*Vec2::new(0.0,0.5), transformed_button_pos- transformed_button_size*Vec2::new(0.0,0.5)],
+                        (2.0, Color32::from_white_alpha(255)),
+                    );
+                    ui.painter().line_segment(
+                        [transformed_button_pos +transformed_button_size*Vec2::new(0.5,0.0), transformed_button_pos -transformed_button_size*Vec2::new(0.5,0.0)],
+                        (2.0, Color32::from_white_alpha(255)),
+                    );
+                }
+
+            }
+
+                    // 使用节点 ID 生成一个稳定的按钮 ID
+            let button_id = Id::new(self.node_id).with("button");
+
+            // 使用稳定的 ID 分配响应
+            let response = ui.interact(rect,button_id, egui::Sense::click());
+            // if response.hovered() {
+            //     println!("Hovering over button of node {:?},response id is {:?}", self.node_id,response.id);
+            // }
+            if response.clicked() {
+                
+                match button_state {
+                    ButtonState::UnFold => ButtonResponse::FoldNode(self.node_id),
+                    ButtonState::Fold => ButtonResponse::UnfoldNode(self.node_id),
+                }
+            } else {
+                ButtonResponse::None
+            }
+    }
+}
+impl Node {
+    //按 100% 缩放排版文字，节点大小等于文字大小加留白，文字超过 max_width 时换行
+    fn fit_text(&mut self, ui: &Ui, text_layout: &TextLayout, style: &ResolvedStyle) {
+        let text_size = self.text_galleys(ui, style, 1.0, text_layout.max_width, Color32::BLACK, false).galley.size();
+        self.node_size = text_layout.node_size(Vector::from(text_size), self.node_shape);
+    }
+    //排版好的文字，存在 egui 的临时内存里，文字、样式、缩放比例、换行宽度和颜色都没变时直接用上次的结果；
+    //测量大小和绘制的参数不同，分开缓存；fake_bold 时同时排版假粗体要再画一遍的部分
+    fn text_galleys(&self, ui: &Ui, style: &ResolvedStyle, scaling: f32, wrap_width: f32, color: Color32, fake_bold: bool) -> TextGalleys {
+        let parsed = self.parsed_text(ui);
+        let key = GalleyKey { style: *style, scaling, wrap_width, color };
+        let id = Id::new(self.node_id).with(("text_galleys", fake_bold));
+        let cached = ui.ctx().data(|d| d.get_temp::<TextGalleys>(id));
+        if let Some(galleys) = cached.filter(|galleys| Arc::ptr_eq(&galleys.parsed, &parsed) && galleys.key == key) {
+            return galleys;
+        }
+        let layout = |bold_only| ui.fonts(|fonts| fonts.layout_job(text_job(&parsed.lines, style, scaling, wrap_width, color, bold_only)));
+        let galley = layout(false);
+        let bold = (fake_bold && (style.bold || parsed.bold)).then(|| layout(true));
+        let galleys = TextGalleys { parsed, key, galley, bold };
+        ui.ctx().data_mut(|d| d.insert_temp(id, galleys.clone()));
+        galleys
+    }
+    //解析过的节点文字，存在 egui 的临时内存里，文字没变时不重新解析
+    fn parsed_text(&self, ui: &Ui) -> Arc<ParsedText> {
+        let id = Id::new(self.node_id).with("parsed_text");
+        let cached = ui.ctx().data(|d| d.get_temp::<Arc<ParsedText>>(id));
+        if let Some(parsed) = cached.filter(|parsed| parsed.text == self.node_text) {
+            return parsed;
+        }
+        let lines = parse_lines(&self.node_text);
+        let bold = lines.iter().flatten().any(|span| span.bold);
+        let parsed = Arc::new(ParsedText { text: self.node_text.clone(), lines, bold });
+        ui.ctx().data_mut(|d| d.insert_temp(id, parsed.clone()));
+        parsed
+    }
+    //拖动节点：屏幕上的位移换算成世界坐标，开启网格对齐时节点中心吸附到网格上
+    //没对齐的位置存在 egui 的临时内存里，这样慢慢拖也能离开当前格子
+    //从外形外面（比如椭圆的角上）开始的拖动不移动节点
+    fn drag(&mut self, ui: &Ui, pan_zoom: &PanZoom, grid: &Canvas, response: &egui::Response, pointer_inside: bool) {
+        let drag_id = Id::new(self.node_id).with("drag");
+        let delta = Vector::from(response.drag_delta() / pan_zoom.transform.scaling);
+        if response.drag_started() {
+            ui.ctx().data_mut(|d| d.remove::<Point>(drag_id));
+            if !pointer_inside {
+                return;
+            }
+        }
+        let start = if response.drag_started() {
+            self.node_pos
+        } else {
+            match ui.ctx().data(|d| d.get_temp::<Point>(drag_id)) {
+                Some(pos) => pos,
+                None => return,
+            }
+        };
+        let free_pos = start + delta;
+        ui.ctx().data_mut(|d| d.insert_temp(drag_id, free_pos));
+        self.node_pos = if grid.snap {
+            grid.snap(free_pos, pan_zoom.transform.scaling)
+        } else {
+            free_pos
+        };
+    }
+}
+//节点文字和解析出的每行文字段
+struct ParsedText {
+    text: String,
+    lines: Vec<Vec<TextSpan>>,
+    //有粗体的文字段，绘制时要错开再画一遍
+    bold: bool,
+}
+
+//排版文字时的参数
+#[derive(Clone, Copy, PartialEq)]
+struct GalleyKey {
+    style: ResolvedStyle,
+    scaling: f32,
+    wrap_width: f32,
+    color: Color32,
+}
+
+//排版好的节点文字，bold 是假粗体要再画一遍的部分，没有粗体时为 None
+#[derive(Clone)]
+struct TextGalleys {
+    parsed: Arc<ParsedText>,
+    key: GalleyKey,
+    galley: Arc<Galley>,
+    bold: Option<Arc<Galley>>,
+}
+
+//按外形画节点，下划线样式只画底边，底边用填充色（有边框时用边框）
+fn paint_shape(ui: &Ui, shape: NodeShape, rect: Rect, fill: Color32, border: Stroke, scaling: f32) {
+    let painter = ui.painter();
+    match shape {
+        NodeShape::RoundedRect => {
+            painter.rect(rect, 5.0, fill, border);
+        }
+        NodeShape::Capsule => {
+            painter.rect(rect, rect.height().min(rect.width()) / 2.0, fill, border);
+        }
+        NodeShape::Ellipse | NodeShape::Diamond => {
+            let segments = (rect.width().max(rect.height()) / 4.0).clamp(16.0, 64.0) as usize;
+            let points = shape.outline(rect.into(), segments).into_iter().map(Pos2::from).collect();
+            painter.add(Shape::convex_polygon(points, fill, border));
+        }
+        NodeShape::Underline => {
+            let stroke = if border.width > 0.0 { border } else { Stroke::new((2.0 * scaling).max(1.0), fill) };
+            painter.line_segment([rect.left_bottom(), rect.right_bottom()], stroke);
+        }
+    }
+}
+
+//节点右上角的备注图标：一张折了角的纸，返回图标占的矩形
+fn paint_note_icon(ui: &Ui, rect: Rect, color: Color32, scaling: f32) -> Rect {
+    let size = Vec2::new(8.0, 10.0) * scaling;
+    let icon = Rect::from_min_size(rect.right_top() + Vec2::new(-size.x - 4.0 * scaling, 4.0 * scaling), size);
+    let fold = size.x * 0.35;
+    let stroke = Stroke::new(scaling.clamp(0.5, 1.5), color);
+    let outline = vec![
+        icon.left_top(),
+        icon.right_top() - Vec2::new(fold, 0.0),
+        icon.right_top() + Vec2::new(0.0, fold),
+        icon.right_bottom(),
+        icon.left_bottom(),
+    ];
+    ui.painter().add(Shape::closed_line(outline, stroke));
+    for i in 1..=2 {
+        let y = icon.top() + fold + i as f32 * (size.y - fold) / 3.0;
+        ui.painter().line_segment([Pos2::new(icon.left() + size.x * 0.2, y), Pos2::new(icon.right() - size.x * 0.2, y)], stroke);
+    }
+    icon
+}
+
+//按节点样式排版文字，Markdown 行内格式分段设置，scaling 为当前缩放比例，测量大小时传 1
+//bold_only 时只有粗体的部分可见，排版和正常的一样，用来错开一点再画一遍做假粗体
+fn text_job(lines: &[Vec<TextSpan>], style: &ResolvedStyle, scaling: f32, wrap_width: f32, color: Color32, bold_only: bool) -> LayoutJob {
+    let font_size = style.font_size * scaling;
+    let base = TextFormat {
+        font_id: FontId::proportional(font_size),
+        color,
+        italics: style.italic,
+        ..TextFormat::default()
+    };
+    let mut job = LayoutJob::default();
+    for (i, spans) in lines.iter().enumerate() {
+        if i > 0 {
+            job.append("\n", 0.0, base.clone());
+        }
+        if spans.is_empty() {
+            job.append("", 0.0, base.clone());
+        }
+        for span in spans {
+            let visible = !bold_only || style.bold || span.bold;
+            let color = if !visible {
+                Color32::TRANSPARENT
+            } else if span.link.is_some() {
+                LINK_COLOR.gamma_multiply(color.a() as f32 / 255.0)
+            } else {
+                color
+            };
+            let line = Stroke::new((font_size / 14.0).max(0.5), color);
+            let format = TextFormat {
+                font_id: if span.code { FontId::monospace(font_size) } else { FontId::proportional(font_size) },
+                color,
+                background: if span.code && !bold_only { color.gamma_multiply(0.15) } else { Color32::TRANSPARENT },
+                italics: style.italic || span.italic,
+                strikethrough: if span.strikethrough { line } else { Stroke::NONE },
+                underline: if span.link.is_some() { line } else { Stroke::NONE },
+                ..TextFormat::default()
+            };
+            job.append(&span.text, 0.0, format);
+        }
+    }
+    job.wrap.max_width = wrap_width;
+    job
+}
+#[derive(Copy,Clone,Debug,PartialEq)]
+pub enum ButtonState{
+   // Hove,
+    Fold,
+    UnFold
+}
@@ -0,0 +1,382 @@
+use std::cmp::PartialEq;
+use std::collections::BTreeMap;
+use std::option::Option;
+use eframe::epaint::Pos2;
+use egui::{Align2, Color32, FontId, Mesh, Shape, Stroke, Ui};
+use egui::epaint::CubicBezierShape;
+use slotmap::SecondaryMap;
+use crate::model::{ConnectorKind, ConnectorStyle, LinkId, NodeGraph, NodeId, Point, Rect, SpatialIndex, SpatialItem};
+use crate::model::connector::{arrow_head, connector_midpoint, connector_points, tapered_outline};
+use crate::model::node_graph::curve_control_points;
+use super::boundary::{draw_boundaries, BoundaryCache};
+use super::guides::snap_to_guides;
+use super::node::{ButtonResponse, ButtonState, NodeResponse, NodeState};
+use super::pan_zoom::PanZoom;
+use super::settings::ViewSettings;
+use super::summary::draw_summaries;
+use super::minimap::MinimapCache;
+use super::traits::{NodeGraphs, View};
+
+#[derive(Default)]
+pub struct GraphResponse{
+    pub nodes_response:SecondaryMap<NodeId, NodeResponse>,
+    pub buttons_response:SecondaryMap<NodeId, ButtonResponse>,
+}
+/// 每个节点的界面状态，用节点在 `NodeGraph.nodes` 里的 id 作为 key
+#[derive(Default)]
+pub struct GraphState{
+    pub node_state: SecondaryMap<NodeId,NodeState>,
+    pub graph_button_state:SecondaryMap<NodeId,ButtonState>,
+    //节点和曲线的空间索引，绘制时只取屏幕内的元素
+    pub spatial_index: SpatialIndex,
+    //选中的关系连线
+    pub selected_link: Option<LinkId>,
+    //正在编辑到父节点连线上文字的节点
+    pub editing_label: Option<NodeId>,
+    //选中的边界，用边界所在的节点表示
+    pub selected_boundary: Option<NodeId>,
+    //缩略图的色块缓存
+    pub minimap_cache: MinimapCache,
+    //边界外框的缓存
+    pub boundary_cache: BoundaryCache,
+    //折叠和隐藏状态的版本，折叠、展开或节点变得可见、不可见时加一
+    fold_revision: u64,
+}
+
+/// 按导图内容缓存的绘制结果（边界外框、缩略图）的 key：空间索引的版本（节点的增删、位置和大小）、
+/// 导图的版本（父节点、样式、边界和概要）和折叠状态的版本，任何一个变了缓存就失效
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheKey {
+    spatial: u64,
+    graph: u64,
+    fold: u64,
+}
+
+impl GraphState {
+    //为已有的文档创建状态，比如从文件读取的导图
+    pub fn from_graph(node_graph: &NodeGraph) -> Self {
+        let mut graph_state = Self::default();
+        graph_state.sync(node_graph);
+        graph_state
+    }
+    //补上缺少的节点状态，去掉已经不存在的节点的状态
+    pub fn sync(&mut self, node_graph: &NodeGraph) {
+        self.node_state.retain(|id, _| node_graph.nodes.contains_key(id));
+        self.graph_button_state.retain(|id, _| node_graph.nodes.contains_key(id));
+        for id in node_graph.nodes.keys() {
+            self.insert_node(id);
+        }
+        self.spatial_index.sync(node_graph);
+        self.selected_link = self.selected_link.filter(|id| node_graph.links.contains_key(*id));
+        self.editing_label = self.editing_label.filter(|id| node_graph.nodes.contains_key(*id));
+        self.selected_boundary = self.selected_boundary.filter(|id| node_graph.nodes.get(*id).is_some_and(|node| node.boundary.is_some()));
+    }
+    pub fn cache_key(&self, node_graph: &NodeGraph) -> CacheKey {
+        CacheKey { spatial: self.spatial_index.revision(), graph: node_graph.revision(), fold: self.fold_revision }
+    }
+    //直接修改了 node_state 的隐藏状态或 graph_button_state 后调用，让按折叠状态缓存的内容失效
+    pub fn fold_changed(&mut self) {
+        self.fold_revision += 1;
+    }
+    fn insert_node(&mut self, node_id: NodeId) {
+        if !self.node_state.contains_key(node_id) {
+            self.node_state.insert(node_id, NodeState::UnSelected);
+        }
+        if !self.graph_button_state.contains_key(node_id) {
+            self.graph_button_state.insert(node_id, ButtonState::UnFold);
+        }
+    }
+    //新增根节点，同时创建它的状态
+    pub fn add_node_with_position(&mut self, node_graph: &mut NodeGraph, pos: Point) -> NodeId {
+        let node_id = node_graph.add_node_with_position(pos);
+        self.insert_node(node_id);
+        node_id
+    }
+    //新增子节点，同时创建它的状态
+    pub fn add_node_with_father_node(&mut self, node_graph: &mut NodeGraph, father_node_id: NodeId) -> NodeId {
+        let node_id = node_graph.add_node_with_father_node(father_node_id);
+        self.insert_node(node_id);
+        node_id
+    }
+    //选中或正在编辑的节点，按 id 排序
+    pub fn selected_nodes(&self) -> Vec<NodeId> {
+        self.node_state.iter()
+            .filter(|(_, state)| matches!(state, NodeState::Selected | NodeState::Editing))
+            .map(|(id, _)| id)
+            .collect()
+    }
+    //只选中这个节点，并展开折叠着它的祖先节点，用于跳转到搜索结果
+    pub fn focus_node(&mut self, node_graph: &NodeGraph, node_id: NodeId) {
+        let ancestors: Vec<NodeId> = node_graph.ancestors_and_self(node_id).skip(1).collect();
+        for ancestor in ancestors.into_iter().rev() {
+            if self.graph_button_state.get(ancestor) == Some(&ButtonState::Fold) {
+                self.graph_button_state[ancestor] = ButtonState::UnFold;
+                for child in node_graph.query_all_children_nodes(ancestor) {
+                    self.node_state.insert(child, NodeState::UnSelected);
+                }
+                self.fold_changed();
+            }
+        }
+        for (_, state) in self.node_state.iter_mut() {
+            if matches!(state, NodeState::Selected | NodeState::Editing) {
+                *state = NodeState::UnSelected;
+            }
+        }
+        self.node_state.insert(node_id, NodeState::Selected);
+    }
+    //删除节点和所有子节点，同时删除它们的状态，返回被删除的节点
+    pub fn remove_node(&mut self, node_graph: &mut NodeGraph, node_id: NodeId) -> Vec<NodeId> {
+        let removed = node_graph.remove_node(node_id);
+        for id in &removed {
+            self.node_state.remove(*id);
+            self.graph_button_state.remove(*id);
+        }
+        removed
+    }
+}
+
+impl PartialEq for NodeState {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (NodeState::Invisible, NodeState::Invisible) => true,
+          //  (NodeState::Visible, NodeState::Visible) => true,
+            (NodeState::Hover, NodeState::Hover) => true,
+            (NodeState::Editing, NodeState::Editing) => true,
+            (NodeState::Selected, NodeState::Selected) => true,
+            (NodeState::UnSelected, NodeState::UnSelected) => true,
+            _ => false,
+        }
+    }
+}
+
+
+//曲线近似成折线时的段数
+pub(super) const CURVE_SEGMENTS: usize = 24;
+//连线文字 100% 缩放时的字号
+const LABEL_FONT_SIZE: f32 = 12.0;
+
+//绘制曲线，curves 里是要画到父节点曲线的子节点，editing_label 是正在编辑连线文字的节点，它的文字由编辑框显示
+pub fn draw_curve_line(node_graph: &NodeGraph, ui: &mut Ui, pan_zoom: &PanZoom, settings: &ViewSettings, curves: &[NodeId], editing_label: Option<NodeId>) {
+    let lod = settings.lod(pan_zoom.transform.scaling);
+
+    for &id in curves {
+        let Some(control) = connector_screen_points(node_graph, pan_zoom, settings, id) else {
+            continue; //没有父节点时，不做任何事情
+        };
+        //颜色跟着分支颜色，走线方式可以按分支设置
+        let connector = node_graph.connector_style(id);
+        if lod.curve <= 0.0 {
+            //缩得很小时直接画直线
+            ui.painter().line_segment([control[0], control[3]], Stroke::new(connector.width, Color32::from(connector.color)));
+        } else {
+            //子节点一端细，父节点一端粗
+            let depth = node_graph.node_depth(id);
+            paint_connector(ui, &connector, control, (connector.width_at(depth), connector.width_at(depth - 1)));
+        }
+        let label = &node_graph.nodes[id].connector_label;
+        if lod.text > 0.0 && !label.is_empty() && editing_label != Some(id) {
+            let center = Pos2::from(connector_midpoint(connector.kind, control.map(Point::from)));
+            let color = Color32::from(connector.color).gamma_multiply(lod.text);
+            paint_label(ui, label, center, color, Color32::from(node_graph.theme.background), pan_zoom.transform.scaling);
+        }
+    }
+}
+//节点到父节点的连线在屏幕上的控制点，已经按缩放级别拉直，画线、放文字和点击检测都用它
+pub(super) fn connector_screen_points(node_graph: &NodeGraph, pan_zoom: &PanZoom, settings: &ViewSettings, node_id: NodeId) -> Option<[Pos2; 4]> {
+    let node = node_graph.nodes.get(node_id)?;
+    let father = node_graph.nodes.get(node.father_id?)?;
+    let screen = curve_control_points(node, father).map(|p| pan_zoom.transform * Pos2::from(p));
+    let bend = settings.lod(pan_zoom.transform.scaling).curve;
+    let curved = matches!(node_graph.connector_style(node_id).kind, ConnectorKind::Bezier | ConnectorKind::Tapered);
+    Some(if bend <= 0.0 || curved { straighten(screen, bend.max(0.0)) } else { screen })
+}
+//在连线中间画一段文字，底下垫一块背景色，盖住文字下面的线
+pub(super) fn paint_label(ui: &Ui, text: &str, center: Pos2, color: Color32, background: Color32, scaling: f32) {
+    let galley = ui.painter().layout_no_wrap(text.to_owned(), FontId::proportional(LABEL_FONT_SIZE * scaling), color);
+    let rect = Align2::CENTER_CENTER.anchor_size(center, galley.size());
+    ui.painter().rect_filled(rect.expand(2.0 * scaling), 2.0 * scaling, background);
+    ui.painter().galley(rect.min, galley, color);
+}
+//线上距离 pos 最近的点是否在 distance 以内，points 是屏幕坐标
+pub(super) fn near_polyline(points: &[Point], pos: Pos2, distance: f32) -> bool {
+    points.windows(2).any(|segment| {
+        let (a, b) = (Pos2::from(segment[0]), Pos2::from(segment[1]));
+        let ab = b - a;
+        let t = if ab.length_sq() > 0.0 { ((pos - a).dot(ab) / ab.length_sq()).clamp(0.0, 1.0) } else { 0.0 };
+        pos.distance(a + ab * t) <= distance
+    })
+}
+//按连线样式画一条屏幕坐标下的连线，tapered_widths 是 Tapered 时两端的线宽，关系连线也用这个函数
+pub(super) fn paint_connector(ui: &Ui, connector: &ConnectorStyle, control: [Pos2; 4], tapered_widths: (f32, f32)) {
+    let color = Color32::from(connector.color);
+    let stroke = Stroke::new(connector.width, color);
+    let points: Vec<Point> = connector_points(connector.kind, control.map(Point::from), CURVE_SEGMENTS);
+    let screen_points: Vec<Pos2> = points.iter().map(|p| Pos2::from(*p)).collect();
+    match connector.kind {
+        _ if connector.dashed => {
+            let (dash, gap) = connector.dash_pattern();
+            ui.painter().extend(Shape::dashed_line(&screen_points, stroke, dash, gap));
+        }
+        ConnectorKind::Bezier => {
+            ui.painter().add(draw_bezier_line(stroke, Color32::TRANSPARENT, control));
+        }
+        ConnectorKind::Tapered => {
+            let outline = tapered_outline(&points, tapered_widths.0, tapered_widths.1);
+            ui.painter().add(strip_mesh(&outline, color));
+        }
+        ConnectorKind::Straight | ConnectorKind::Elbow => {
+            ui.painter().add(Shape::line(screen_points, stroke));
+        }
+    }
+    if connector.arrow {
+        if let Some(head) = arrow_head(points[0], points[1], connector.arrow_size()) {
+            ui.painter().add(Shape::convex_polygon(head.map(Pos2::from).to_vec(), color, Stroke::NONE));
+        }
+    }
+}
+//tapered_outline 返回的轮廓前一半是一侧的点，后一半倒序是另一侧，两侧对应的点连成三角形带
+fn strip_mesh(outline: &[Point], color: Color32) -> Mesh {
+    let mut mesh = Mesh::default();
+    let n = outline.len() / 2;
+    for p in outline {
+        mesh.colored_vertex(Pos2::from(*p), color);
+    }
+    for i in 0..n.saturating_sub(1) {
+        let (left, right) = (i as u32, (2 * n - 1 - i) as u32);
+        mesh.add_triangle(left, right, left + 1);
+        mesh.add_triangle(left + 1, right, right - 1);
+    }
+    mesh
+}
+//把中间两个控制点往首尾连线上拉，bend 为 0 时变成直线，1 时保持原样
+fn straighten([p0, p1, p2, p3]: [Pos2; 4], bend: f32) -> [Pos2; 4] {
+    let line1 = p0.lerp(p3, 1.0 / 3.0);
+    let line2 = p0.lerp(p3, 2.0 / 3.0);
+    [p0, line1.lerp(p1, bend), line2.lerp(p2, bend), p3]
+}
+//缩得很小时，把比 summary_depth 更深的节点按所在子树合并成色块
+//返回每个子树（用 summary_depth 层的祖先表示）在屏幕内部分的包围盒
+fn summary_blobs(node_graph: &NodeGraph, nodes: &[NodeId], summary_depth: usize) -> BTreeMap<NodeId, Rect> {
+    let mut blobs: BTreeMap<NodeId, Rect> = BTreeMap::new();
+    for &id in nodes {
+        if node_graph.node_depth(id) <= summary_depth {
+            continue;
+        }
+        if let Some(ancestor) = node_graph.ancestor_at_depth(id, summary_depth) {
+            let blob = blobs.entry(ancestor).or_insert(Rect::NOTHING);
+            *blob = blob.union(node_graph.nodes[id].rect());
+        }
+    }
+    blobs
+}
+//判断节点是否应该绘制：自己不是隐藏状态，且所有祖先都没有折叠
+pub fn is_node_visible(node_graph: &NodeGraph, node_id: NodeId, graph_state: &GraphState) -> bool {
+    if graph_state.node_state.get(node_id) == Some(&NodeState::Invisible){
+        //如果节点是不可见状态，则不绘制
+        return false;
+    }
+    let mut current_node_id = node_graph.nodes.get(node_id).and_then(|node| node.father_id);
+    let mut root_id = node_id;
+    let mut steps = 0;
+    while let Some(father_id) = current_node_id {
+        root_id = father_id;
+        //如果有父节点 且是折叠状态，则不绘制
+        if graph_state.graph_button_state.get(father_id) == Some(&ButtonState::Fold) {
+            return false;
+        }
+        current_node_id = node_graph.nodes.get(father_id).and_then(|node| node.father_id);//更新current_node_id
+        steps += 1;
+        if steps > node_graph.nodes.len() {
+            break;
+        }
+    }
+    //概要节点和它下面的节点跟着括起来的节点一起折叠
+    match node_graph.summary_of_topic(root_id).and_then(|id| node_graph.summaries.get(id)) {
+        //括起来的节点在概要节点下面时不再往上找，避免无限递归
+        Some(summary) if node_graph.ancestors_and_self(summary.from).all(|id| id != root_id) => {
+            is_node_visible(node_graph, summary.from, graph_state)
+        }
+        _ => true,
+    }
+}
+//屏幕内（clip rect 反变换到世界坐标）需要绘制的节点和曲线，按节点 id 排序保证绘制顺序稳定
+pub fn on_screen_items(node_graph: &NodeGraph, ui: &Ui, pan_zoom: &PanZoom, graph_state: &GraphState) -> (Vec<NodeId>, Vec<NodeId>) {
+    let view_rect = Rect::from(pan_zoom.transform.inverse() * ui.clip_rect());
+    let mut nodes = Vec::new();
+    let mut curves = Vec::new();
+    for item in graph_state.spatial_index.query(view_rect) {
+        match item {
+            SpatialItem::Node(id) => nodes.push(id),
+            SpatialItem::Curve(id) => curves.push(id),
+        }
+    }
+    for ids in [&mut nodes, &mut curves] {
+        ids.retain(|id| node_graph.nodes.contains_key(*id) && is_node_visible(node_graph, *id, graph_state));
+        ids.sort_unstable();
+    }
+    (nodes, curves)
+}
+
+impl NodeGraphs for NodeGraph{
+    fn draw(&mut self, ui: &mut Ui, pan_zoom: &mut PanZoom, settings: &ViewSettings, graph_state: &mut GraphState) -> GraphResponse {
+        let mut graph_response = GraphResponse::default();
+
+         // 只收集屏幕内需要绘制的节点 ID，屏幕外的节点不布局、不绘制、不做命中检测
+         let (mut nodes_to_draw, mut curves_to_draw) = on_screen_items(self, ui, pan_zoom, graph_state);
+
+        // 缩得很小时深层子树合并成色块，色块完全不透明后深层节点就不用再画了
+        let lod = settings.lod(pan_zoom.transform.scaling);
+        let summary_depth = settings.lod_settings.summary_depth;
+        let blobs = if lod.summary > 0.0 { summary_blobs(self, &nodes_to_draw, summary_depth) } else { BTreeMap::new() };
+        if lod.summary >= 1.0 {
+            nodes_to_draw.retain(|id| self.node_depth(*id) <= summary_depth);
+            curves_to_draw.retain(|id| self.node_depth(*id) <= summary_depth);
+        }
+
+        draw_boundaries(ui, self, graph_state, pan_zoom, settings);
+        draw_summaries(ui, self, graph_state, pan_zoom);
+
+        // 绘制节点，位置变了说明节点正在被拖动
+        let mut dragged = None;
+        for &node_id in &nodes_to_draw {
+         let defaults = self.default_style(node_id);
+         if let Some(node) = self.nodes.get_mut(node_id) {
+             let old_pos = node.node_pos;
+             let response = node.draw(ui, pan_zoom, settings, &mut graph_state.node_state[node_id], &defaults, &self.theme);
+             graph_response.nodes_response.insert(node_id, response);
+             if node.node_pos != old_pos {
+                 dragged = Some(node_id);
+             }
+            }
+        }
+        if let Some(node_id) = dragged.filter(|_| settings.smart_guides) {
+            snap_to_guides(self, node_id, &nodes_to_draw, ui, pan_zoom);
+        }
+
+        for (ancestor, blob) in &blobs {
+            let color = Color32::from(self.node_style(*ancestor).fill).gamma_multiply(lod.summary);
+            let rect = pan_zoom.transform * egui::Rect::from(blob.expand(10.0));
+            ui.painter().rect_filled(rect, 10.0 * pan_zoom.transform.scaling, color);
+        }
+
+        // 绘制有子节点的节点的展开按钮并收集响应，只画色块时按钮也不画
+        for &id in &nodes_to_draw {
+            if !self.has_children(id) || lod.text <= 0.0 {
+                continue;
+            }
+            let defaults = self.default_style(id);
+            //按钮连线接着子节点的连线，Tapered 时用这一层的线宽
+            let mut connector = self.connector_style(id);
+            connector.width = connector.width_at(self.node_depth(id));
+            if let Some(node) = self.nodes.get_mut(id) {
+                let button_response = node.draw_button(ui, pan_zoom, &mut graph_state.graph_button_state[id], &defaults, &connector);
+                graph_response.buttons_response.insert(id, button_response);
+            }
+        }
+        draw_curve_line(self,ui,pan_zoom,settings,&curves_to_draw,graph_state.editing_label);
+        graph_response
+    }
+}
+pub fn draw_bezier_line( stroke:Stroke,color:Color32,control_point:[Pos2; 4])->CubicBezierShape{
+    CubicBezierShape::from_points_stroke(control_point,false,color,stroke)
+}
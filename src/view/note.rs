@@ -0,0 +1,47 @@
+//! 节点备注的编辑面板和按文字、备注搜索节点的结果列表
+
+use egui::{ScrollArea, TextEdit, Ui};
+use crate::model::{NodeGraph, NodeId};
+
+//结果列表里节点文字最多显示的字数
+const RESULT_TEXT_LEN: usize = 40;
+
+//编辑节点的备注（Markdown），内容变化时返回 true
+pub fn note_panel(ui: &mut Ui, node_graph: &mut NodeGraph, node_id: NodeId) -> bool {
+    let Some(node) = node_graph.nodes.get_mut(node_id) else {
+        return false;
+    };
+    let editor = TextEdit::multiline(&mut node.note)
+        .id_salt(("note", node_id))
+        .hint_text("Markdown")
+        .desired_rows(8)
+        .desired_width(f32::INFINITY);
+    ui.add(editor).changed()
+}
+
+//列出文字或备注里包含 query 的节点，返回被点击的节点
+pub fn search_results(ui: &mut Ui, node_graph: &NodeGraph, query: &str) -> Option<NodeId> {
+    let found = node_graph.search(query);
+    if found.is_empty() {
+        ui.label("No matches");
+        return None;
+    }
+    let mut clicked = None;
+    ScrollArea::vertical().show(ui, |ui| {
+        for id in found {
+            let node = &node_graph.nodes[id];
+            let mut text: String = node.node_text.split_whitespace().collect::<Vec<_>>().join(" ");
+            if text.chars().count() > RESULT_TEXT_LEN {
+                text = text.chars().take(RESULT_TEXT_LEN).chain(std::iter::once('…')).collect();
+            }
+            let mut response = ui.selectable_label(false, text);
+            if !node.note.is_empty() {
+                response = response.on_hover_text(node.note.as_str());
+            }
+            if response.clicked() {
+                clicked = Some(id);
+            }
+        }
+    });
+    clicked
+}
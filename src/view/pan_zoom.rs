@@ -0,0 +1,272 @@
+use eframe::emath::{Pos2, Rect, TSTransform, Vec2};
+use egui::Context;
+use crate::model::{NodeGraph, Point};
+use super::node_graph::GraphState;
+
+/// 键盘缩放每次的倍数
+pub const ZOOM_STEP: f32 = 1.25;
+/// 缩放到整个导图或选中节点时四周留白（屏幕像素）
+pub const FIT_PADDING: f32 = 40.0;
+
+/// 镜头从当前位置移动到目标位置的过程，开始时间在第一次 `animate` 时记录
+#[derive(Debug, Clone, Copy)]
+struct CameraAnimation {
+    from: TSTransform,
+    to: TSTransform,
+    start: Option<f64>,
+}
+
+#[derive(Debug)]
+pub struct PanZoom{
+   pub transform: TSTransform,
+   //缩放比例的范围
+   pub min_scaling: f32,
+   pub max_scaling: f32,
+   //上一帧控件在屏幕上的区域，缩放命令以它的中心为准
+   pub viewport: Rect,
+   //缩放、跳转等命令的镜头动画时长（秒），为 0 时直接跳到目标位置
+   pub animation_duration: f32,
+   animation: Option<CameraAnimation>,
+}
+impl PanZoom{
+    pub fn new() -> Self {
+        Self::default()
+    }
+    //以屏幕上的某个点为中心缩放，缩放比例限制在 min_scaling..=max_scaling
+    //滚轮缩放用这个，立即生效并打断正在进行的动画
+    pub fn zoom_around(&mut self, factor: f32, screen_pos: Pos2) {
+        self.animation = None;
+        self.transform = self.zoomed_around(self.transform, factor, screen_pos);
+    }
+    //拖动画布平移，同样打断动画
+    pub fn pan(&mut self, delta: Vec2) {
+        self.animation = None;
+        self.transform.translation += delta;
+    }
+    //以视口中心放大
+    pub fn zoom_in(&mut self) {
+        let to = self.zoomed_around(self.target(), ZOOM_STEP, self.viewport.center());
+        self.animate_to(to);
+    }
+    //以视口中心缩小
+    pub fn zoom_out(&mut self) {
+        let to = self.zoomed_around(self.target(), 1.0 / ZOOM_STEP, self.viewport.center());
+        self.animate_to(to);
+    }
+    //恢复到 100%，视口中心的内容保持不动
+    pub fn reset_zoom(&mut self) {
+        let target = self.target();
+        let to = self.zoomed_around(target, 1.0 / target.scaling, self.viewport.center());
+        self.animate_to(to);
+    }
+    //让世界坐标下的矩形完整显示在视口里
+    pub fn zoom_to_rect(&mut self, world_rect: crate::model::Rect) {
+        let viewport = self.viewport.shrink(FIT_PADDING);
+        if !viewport.is_positive() || world_rect.is_negative() {
+            return;
+        }
+        //只有一个点时不用放大到极限，保持当前比例
+        let scaling = if world_rect.width() > 0.0 || world_rect.height() > 0.0 {
+            (viewport.width() / world_rect.width()).min(viewport.height() / world_rect.height())
+        } else {
+            self.target().scaling
+        };
+        let scaling = self.clamp_scaling(scaling);
+        let center = Pos2::from(world_rect.center());
+        self.animate_to(TSTransform::new(viewport.center().to_vec2() - center.to_vec2() * scaling, scaling));
+    }
+    //保持缩放比例，把世界坐标下的点移到视口中心，用于跳转到搜索结果等节点
+    pub fn center_on(&mut self, world_pos: Point) {
+        let scaling = self.target().scaling;
+        let to = TSTransform::new(self.viewport.center().to_vec2() - Pos2::from(world_pos).to_vec2() * scaling, scaling);
+        self.animate_to(to);
+    }
+    //立即把世界坐标下的点移到视口中心，用于拖动缩略图
+    pub fn set_center(&mut self, world_pos: Point) {
+        self.animation = None;
+        let scaling = self.transform.scaling;
+        self.transform = TSTransform::new(self.viewport.center().to_vec2() - Pos2::from(world_pos).to_vec2() * scaling, scaling);
+    }
+    //显示整个导图
+    pub fn zoom_to_fit(&mut self, node_graph: &NodeGraph) {
+        if let Some(bounds) = node_graph.bounds() {
+            self.zoom_to_rect(bounds);
+        }
+    }
+    //显示所有选中的节点
+    pub fn zoom_to_selection(&mut self, node_graph: &NodeGraph, graph_state: &GraphState) {
+        if let Some(bounds) = node_graph.bounds_of(graph_state.selected_nodes()) {
+            self.zoom_to_rect(bounds);
+        }
+    }
+    //动画结束后的位置，没有动画时就是当前位置；连续的命令以它为基础叠加
+    pub fn target(&self) -> TSTransform {
+        self.animation.map_or(self.transform, |animation| animation.to)
+    }
+    pub fn is_animating(&self) -> bool {
+        self.animation.is_some()
+    }
+    //停在当前位置
+    pub fn stop_animation(&mut self) {
+        self.animation = None;
+    }
+    //平滑移动到目标位置，实际的移动在 animate 里逐帧进行
+    pub fn animate_to(&mut self, to: TSTransform) {
+        if self.animation_duration <= 0.0 {
+            self.animation = None;
+            self.transform = to;
+            return;
+        }
+        self.animation = Some(CameraAnimation { from: self.transform, to, start: None });
+    }
+    //每帧调用一次，推进动画；只有动画进行中才请求重绘
+    pub fn animate(&mut self, ctx: &Context) {
+        let Some(animation) = &mut self.animation else {
+            return;
+        };
+        let now = ctx.input(|i| i.time);
+        let start = *animation.start.get_or_insert(now);
+        let t = ((now - start) as f32 / self.animation_duration).clamp(0.0, 1.0);
+        if t >= 1.0 {
+            self.transform = animation.to;
+            self.animation = None;
+            return;
+        }
+        self.transform = interpolate(animation.from, animation.to, ease_in_out(t), self.viewport.center());
+        ctx.request_repaint();
+    }
+    fn zoomed_around(&self, transform: TSTransform, factor: f32, screen_pos: Pos2) -> TSTransform {
+        let pointer_in_layer = transform.inverse() * screen_pos;
+        let scaling = self.clamp_scaling(transform.scaling * factor);
+        TSTransform::new(screen_pos.to_vec2() - pointer_in_layer.to_vec2() * scaling, scaling)
+    }
+    pub fn clamp_scaling(&self, scaling: f32) -> f32 {
+        scaling.clamp(self.min_scaling, self.max_scaling)
+    }
+}
+impl Default for PanZoom{
+    fn default() -> Self {
+        PanZoom {
+            transform: TSTransform::default(),
+            min_scaling: 0.02,
+            max_scaling: 10.0,
+            viewport: Rect::ZERO,
+            animation_duration: 0.3,
+            animation: None,
+        }
+    }
+}
+
+//先加速后减速
+fn ease_in_out(t: f32) -> f32 {
+    if t < 0.5 {
+        4.0 * t * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+    }
+}
+
+//缩放比例按对数插值，视口中心对准的世界坐标按直线插值，这样放大和缩小看起来速度一致
+fn interpolate(from: TSTransform, to: TSTransform, t: f32, screen_center: Pos2) -> TSTransform {
+    let from_center = from.inverse() * screen_center;
+    let to_center = to.inverse() * screen_center;
+    let scaling = (from.scaling.ln() + (to.scaling.ln() - from.scaling.ln()) * t).exp();
+    let center = from_center.lerp(to_center, t);
+    TSTransform::new(screen_center.to_vec2() - center.to_vec2() * scaling, scaling)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //800x600 的视口，不做动画
+    fn pan_zoom() -> PanZoom {
+        PanZoom { viewport: Rect::from_min_size(Pos2::ZERO, Vec2::new(800.0, 600.0)), animation_duration: 0.0, ..PanZoom::default() }
+    }
+
+    #[test]
+    fn zoom_stays_within_limits() {
+        let mut pan_zoom = pan_zoom();
+        for _ in 0..100 {
+            pan_zoom.zoom_in();
+        }
+        assert_eq!(pan_zoom.transform.scaling, pan_zoom.max_scaling);
+        for _ in 0..100 {
+            pan_zoom.zoom_around(0.5, Pos2::new(10.0, 10.0));
+        }
+        assert_eq!(pan_zoom.transform.scaling, pan_zoom.min_scaling);
+        pan_zoom.reset_zoom();
+        assert_eq!(pan_zoom.transform.scaling, 1.0);
+    }
+
+    #[test]
+    fn zoom_around_keeps_the_point_under_the_pointer() {
+        let mut pan_zoom = pan_zoom();
+        let pointer = Pos2::new(300.0, 200.0);
+        let world = pan_zoom.transform.inverse() * pointer;
+        pan_zoom.zoom_around(2.0, pointer);
+        assert!((pan_zoom.transform * world).distance(pointer) < 1e-3);
+    }
+
+    #[test]
+    fn zoom_to_rect_fits_the_rect_inside_the_padding() {
+        let mut pan_zoom = pan_zoom();
+        let world = crate::model::Rect::from_min_max(Point::new(-100.0, -50.0), Point::new(1900.0, 450.0));
+        pan_zoom.zoom_to_rect(world);
+        let screen = pan_zoom.transform * Rect::from(world);
+        let viewport = pan_zoom.viewport.shrink(FIT_PADDING);
+        assert!((screen.width() - viewport.width()).abs() < 1e-3);
+        assert!(screen.height() <= viewport.height());
+        assert!((screen.center() - viewport.center()).length() < 1e-3);
+        //太大的导图也不会缩小到下限以下
+        pan_zoom.zoom_to_rect(crate::model::Rect::from_min_max(Point::ZERO, Point::new(1e9, 1e9)));
+        assert_eq!(pan_zoom.transform.scaling, pan_zoom.min_scaling);
+    }
+
+    #[test]
+    fn easing_starts_and_ends_slowly() {
+        assert_eq!(ease_in_out(0.0), 0.0);
+        assert_eq!(ease_in_out(0.5), 0.5);
+        assert_eq!(ease_in_out(1.0), 1.0);
+        let steps: Vec<f32> = (0..=10).map(|i| ease_in_out(i as f32 / 10.0)).collect();
+        assert!(steps.windows(2).all(|w| w[0] <= w[1]));
+        assert!(steps[1] - steps[0] < steps[6] - steps[5]);
+    }
+
+    #[test]
+    fn interpolation_hits_both_ends_and_scales_logarithmically() {
+        let center = Pos2::new(400.0, 300.0);
+        let from = TSTransform::new(Vec2::new(10.0, 20.0), 0.5);
+        let to = TSTransform::new(Vec2::new(-300.0, 40.0), 2.0);
+        assert_eq!(interpolate(from, to, 0.0, center).scaling, from.scaling);
+        let end = interpolate(from, to, 1.0, center);
+        assert!((end.scaling - to.scaling).abs() < 1e-5 && (end.translation - to.translation).length() < 1e-3);
+        //0.5 到 2 的中间是 1
+        assert!((interpolate(from, to, 0.5, center).scaling - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn animations_run_frame_by_frame_and_commands_stack_on_the_target() {
+        let mut pan_zoom = PanZoom { animation_duration: 1.0, ..pan_zoom() };
+        pan_zoom.zoom_in();
+        pan_zoom.zoom_in();
+        assert!(pan_zoom.is_animating());
+        assert_eq!(pan_zoom.transform.scaling, 1.0);
+        assert!((pan_zoom.target().scaling - ZOOM_STEP * ZOOM_STEP).abs() < 1e-5);
+        let ctx = Context::default();
+        let frame = |time: f64, pan_zoom: &mut PanZoom| {
+            let input = egui::RawInput { time: Some(time), ..Default::default() };
+            let _ = ctx.run(input, |ctx| pan_zoom.animate(ctx));
+        };
+        frame(0.0, &mut pan_zoom);
+        frame(0.5, &mut pan_zoom);
+        assert!(pan_zoom.transform.scaling > 1.0 && pan_zoom.transform.scaling < pan_zoom.target().scaling);
+        frame(1.0, &mut pan_zoom);
+        assert!(!pan_zoom.is_animating());
+        assert!((pan_zoom.transform.scaling - ZOOM_STEP * ZOOM_STEP).abs() < 1e-5);
+        //拖动画布打断动画
+        pan_zoom.zoom_out();
+        pan_zoom.pan(Vec2::new(5.0, 0.0));
+        assert!(!pan_zoom.is_animating());
+    }
+}
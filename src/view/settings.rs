@@ -0,0 +1,37 @@
+//! 导图的显示设置：背景网格、对齐参考线、文字排版和缩小时的细节层级
+//!
+//! 和 `PanZoom` 分开：`PanZoom` 只管镜头的位置、缩放范围和动画，这里是不随镜头变化的选项。
+
+use crate::model::TextLayout;
+use super::canvas::Canvas;
+use super::lod::{Lod, LodSettings};
+
+#[derive(Debug, Clone, Copy)]
+pub struct ViewSettings {
+    /// 世界坐标下的背景网格，拖动节点时也用它对齐
+    pub grid: Canvas,
+    /// 拖动节点时显示和附近节点对齐的参考线
+    pub smart_guides: bool,
+    /// 节点文字的字号、换行宽度，以及节点是否跟随文字调整大小
+    pub text_layout: TextLayout,
+    /// 缩小时的细节层级设置
+    pub lod_settings: LodSettings,
+}
+
+impl Default for ViewSettings {
+    fn default() -> Self {
+        Self {
+            grid: Canvas::default(),
+            smart_guides: true,
+            text_layout: TextLayout::default(),
+            lod_settings: LodSettings::default(),
+        }
+    }
+}
+
+impl ViewSettings {
+    //缩放比例 scaling 对应的细节层级
+    pub fn lod(&self, scaling: f32) -> Lod {
+        self.lod_settings.lod(scaling)
+    }
+}
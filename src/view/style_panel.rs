@@ -0,0 +1,80 @@
+//! 节点样式面板：每个属性可以勾选后单独设置，不勾选时使用默认样式
+
+use egui::{Color32, ComboBox, DragValue, Grid, Response, Ui};
+use crate::model::{ConnectorKind, NodeGraph, NodeId, NodeStyle, ResolvedStyle};
+
+/// 编辑一个样式，返回是否有修改
+pub fn style_editor(ui: &mut Ui, style: &mut NodeStyle, defaults: &ResolvedStyle) -> bool {
+    let mut changed = false;
+    Grid::new("node_style").num_columns(2).show(ui, |ui| {
+        changed |= optional(ui, "Fill", &mut style.fill, defaults.fill, color_button);
+        changed |= optional(ui, "Border", &mut style.border_color, defaults.border_color, color_button);
+        changed |= optional(ui, "Border width", &mut style.border_width, defaults.border_width, |ui, width| {
+            ui.add(DragValue::new(width).range(0.0..=20.0).speed(0.1))
+        });
+        changed |= optional(ui, "Text color", &mut style.text_color, defaults.text_color, color_button);
+        changed |= optional(ui, "Font size", &mut style.font_size, defaults.font_size, |ui, size| {
+            ui.add(DragValue::new(size).range(6.0..=96.0).speed(0.2))
+        });
+        changed |= optional(ui, "Bold", &mut style.bold, defaults.bold, |ui, bold| ui.checkbox(bold, ""));
+        changed |= optional(ui, "Italic", &mut style.italic, defaults.italic, |ui, italic| ui.checkbox(italic, ""));
+        changed |= optional(ui, "Branch color", &mut style.branch_color, defaults.fill, color_button);
+        changed |= optional(ui, "Connector", &mut style.connector, ConnectorKind::default(), connector_combo);
+    });
+    changed
+}
+
+/// 编辑多个节点的样式：面板显示第一个节点的样式，修改的属性应用到所有节点上，没修改的属性各自保留
+pub fn style_panel(ui: &mut Ui, node_graph: &mut NodeGraph, nodes: &[NodeId], defaults: &ResolvedStyle) -> bool {
+    let Some(before) = nodes.first().and_then(|id| node_graph.nodes.get(*id)).map(|node| node.node_style) else {
+        ui.label("Select a node to edit its style");
+        return false;
+    };
+    let mut after = before;
+    if !style_editor(ui, &mut after, defaults) {
+        return false;
+    }
+    for &id in nodes {
+        if let Some(node) = node_graph.nodes.get_mut(id) {
+            node.node_style.apply_changes(&before, &after);
+        }
+    }
+    node_graph.touch();
+    true
+}
+
+//一行属性：勾选框决定是否覆盖默认值，勾选后才能编辑
+fn optional<T: Copy>(ui: &mut Ui, label: &str, value: &mut Option<T>, default: T, edit: impl FnOnce(&mut Ui, &mut T) -> Response) -> bool {
+    let mut overridden = value.is_some();
+    let mut changed = ui.checkbox(&mut overridden, label).changed();
+    let mut current = value.unwrap_or(default);
+    ui.add_enabled_ui(overridden, |ui| {
+        changed |= edit(ui, &mut current).changed();
+    });
+    *value = overridden.then_some(current);
+    ui.end_row();
+    changed
+}
+
+fn color_button(ui: &mut Ui, color: &mut crate::model::Color) -> Response {
+    let mut color32 = Color32::from(*color);
+    let response = ui.color_edit_button_srgba(&mut color32);
+    *color = color32.into();
+    response
+}
+
+fn connector_combo(ui: &mut Ui, kind: &mut ConnectorKind) -> Response {
+    let mut changed = false;
+    let mut response = ComboBox::from_id_salt("connector_kind")
+        .selected_text(kind.name())
+        .show_ui(ui, |ui| {
+            for option in ConnectorKind::ALL {
+                changed |= ui.selectable_value(kind, option, option.name()).changed();
+            }
+        })
+        .response;
+    if changed {
+        response.mark_changed();
+    }
+    response
+}
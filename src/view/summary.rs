@@ -0,0 +1,25 @@
+//! 概要的括号和括号到概要节点的连线，画在节点下面
+
+use egui::{Color32, Pos2, Shape, Stroke, Ui};
+use crate::model::summary::{brace_points, brace_tip};
+use crate::model::{NodeGraph, Point};
+use super::node_graph::{is_node_visible, GraphState};
+use super::pan_zoom::PanZoom;
+
+//括起来的节点都折叠起来时不画；括号颜色和线宽跟第一个括起来的节点的连线一样
+pub fn draw_summaries(ui: &Ui, node_graph: &NodeGraph, graph_state: &GraphState, pan_zoom: &PanZoom) {
+    let transform = pan_zoom.transform;
+    let visible = |id| is_node_visible(node_graph, id, graph_state);
+    for (id, summary) in &node_graph.summaries {
+        let Some((bounds, side)) = node_graph.summary_brace(id, &visible) else { continue };
+        let connector = node_graph.connector_style(summary.from);
+        let stroke = Stroke::new(connector.width, Color32::from(connector.color));
+        let points: Vec<Pos2> = brace_points(bounds, side).into_iter().map(|p| transform * Pos2::from(p)).collect();
+        ui.painter().add(Shape::line(points, stroke));
+        if let Some(topic) = node_graph.nodes.get(summary.topic).filter(|_| visible(summary.topic)) {
+            let rect = topic.rect();
+            let edge = Point::new(if side > 0.0 { rect.min.x } else { rect.max.x }, rect.center().y);
+            ui.painter().line_segment([transform * Pos2::from(brace_tip(bounds, side)), transform * Pos2::from(edge)], stroke);
+        }
+    }
+}
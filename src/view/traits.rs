@@ -0,0 +1,29 @@
+use egui::{Response, Ui};
+use crate::model::{ConnectorStyle, ResolvedStyle, Theme};
+use super::node::{ButtonResponse, ButtonState, NodeResponse, NodeState};
+use super::node_graph::{GraphResponse, GraphState};
+use super::pan_zoom::PanZoom;
+use super::settings::ViewSettings;
+
+pub trait NodeGraphs {
+    //用draw 函数实现node graph的绘制逻辑
+    fn draw(&mut self, _ui: &mut Ui, _pan_zoom: &mut PanZoom, _settings: &ViewSettings, _graph_state: &mut GraphState)-> GraphResponse{
+      GraphResponse::default()
+    }
+}
+pub trait View {
+    //这个函数用来实现node 的绘制
+    //defaults 是主题给这个节点的默认样式
+    fn draw(&mut self, _ui: &mut Ui, _pan_zoom: &mut PanZoom, _settings: &ViewSettings, _node_state: &mut NodeState, _defaults: &ResolvedStyle, _theme: &Theme)->NodeResponse{
+        NodeResponse::None
+    }
+    //下面这个暂时不用了
+    fn ui(&mut self, ui: &mut Ui, _pan_zoom: &mut PanZoom) -> Response {
+        let (id, rect) = ui.allocate_space(ui.available_size());
+        ui.interact(rect, id, egui::Sense::click_and_drag())
+    }
+    //connector 是节点和按钮之间连线的样式
+    fn draw_button(&mut self, _ui: &mut Ui, _pan_zoom: &mut PanZoom, _button_state: &mut ButtonState, _defaults: &ResolvedStyle, _connector: &ConnectorStyle)->ButtonResponse{
+        ButtonResponse::None
+    }
+}